@@ -0,0 +1,241 @@
+//! Derive macros for the sondbus crate.
+//!
+//! See `sondbus::layout` for the generated items and their semantics.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitInt};
+
+/// The access to a field, as given by its attributes
+enum Access {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+/// A field of the struct, as given by its attributes
+struct Field {
+    ident: syn::Ident,
+    ty: syn::Type,
+    offset: Option<LitInt>,
+    access: Access,
+}
+
+/// Derives the memory layout of a slave from a struct.
+///
+/// Every field is placed in the memory area right after the previous one,
+/// unless it specifies its own offset using `#[sondbus(offset = 0x10)]`.
+/// Fields can be restricted using `#[sondbus(read_only)]` or `#[sondbus(write_only)]`.
+///
+/// This generates:
+/// * A `sondbus::layout::Field` constant for every field, named after the field in upper case
+/// * An implementation of `sondbus::layout::MemoryLayout`
+/// * An implementation of `sondbus::slave::transceiver::SlaveMemory`
+#[proc_macro_derive(SondbusMemory, attributes(sondbus))]
+pub fn derive_sondbus_memory(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "SondbusMemory cannot be derived for generic structs",
+        ));
+    }
+
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input,
+            "SondbusMemory can only be derived for structs",
+        ));
+    };
+
+    let Fields::Named(named) = &data.fields else {
+        return Err(Error::new_spanned(
+            &input,
+            "SondbusMemory requires a struct with named fields",
+        ));
+    };
+
+    let fields = named
+        .named
+        .iter()
+        .map(parse_field)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if fields.is_empty() {
+        return Err(Error::new_spanned(
+            &input,
+            "SondbusMemory requires at least one field",
+        ));
+    }
+
+    let consts: Vec<_> = fields
+        .iter()
+        .map(|f| format_ident!("{}", f.ident.to_string().to_uppercase()))
+        .collect();
+
+    let mut field_consts = Vec::new();
+    let mut infos = Vec::new();
+    let mut stores = Vec::new();
+    let mut loads = Vec::new();
+    let mut asserts = Vec::new();
+
+    for (i, (f, c)) in fields.iter().zip(&consts).enumerate() {
+        let ident = &f.ident;
+        let ty = &f.ty;
+
+        let offset = match (&f.offset, i) {
+            (Some(offset), _) => quote!(#offset),
+            (None, 0) => quote!(0),
+            (None, _) => {
+                let prev = &consts[i - 1];
+                quote!(Self::#prev.offset + Self::#prev.size())
+            }
+        };
+
+        let access = match f.access {
+            Access::ReadOnly => quote!(::sondbus::layout::Access::ReadOnly),
+            Access::WriteOnly => quote!(::sondbus::layout::Access::WriteOnly),
+            Access::ReadWrite => quote!(::sondbus::layout::Access::ReadWrite),
+        };
+
+        field_consts.push(quote! {
+            pub const #c: ::sondbus::layout::Field<#ty> =
+                ::sondbus::layout::Field::new(#offset, #access);
+        });
+
+        infos.push(quote! {
+            ::sondbus::layout::FieldInfo {
+                offset: Self::#c.offset,
+                size: Self::#c.size(),
+                access: Self::#c.access,
+            }
+        });
+
+        stores.push(quote! {
+            {
+                let start = Self::#c.offset as usize;
+                let end = start + Self::#c.size() as usize;
+                ::sondbus::layout::FieldValue::to_bytes(&self.#ident, &mut buf[start..end]);
+            }
+        });
+
+        loads.push(quote! {
+            {
+                let start = Self::#c.offset as usize;
+                let end = start + Self::#c.size() as usize;
+                if start < offset as usize + len && (offset as usize) < end {
+                    self.#ident = ::sondbus::layout::FieldValue::from_bytes(&buf[start..end]);
+                }
+            }
+        });
+
+        if i > 0 {
+            let prev = &consts[i - 1];
+            let msg = format!(
+                "field `{}` overlaps or precedes the previous field",
+                f.ident
+            );
+            asserts.push(quote! {
+                assert!(
+                    #name::#c.offset as usize
+                        >= #name::#prev.offset as usize + #name::#prev.size() as usize,
+                    #msg
+                );
+            });
+        }
+    }
+
+    let last = consts.last().unwrap();
+
+    Ok(quote! {
+        impl #name {
+            #(#field_consts)*
+        }
+
+        const _: () = {
+            #(#asserts)*
+        };
+
+        impl ::sondbus::layout::MemoryLayout for #name {
+            const SIZE: usize = Self::#last.offset as usize + Self::#last.size() as usize;
+
+            const FIELDS: &'static [::sondbus::layout::FieldInfo] = &[#(#infos),*];
+
+            fn store(&self, buf: &mut [u8]) {
+                #(#stores)*
+            }
+
+            fn load(&mut self, buf: &[u8], offset: u16, len: usize) {
+                #(#loads)*
+            }
+        }
+
+        impl ::sondbus::slave::transceiver::SlaveMemory for #name {
+            fn read(
+                &mut self,
+                offset: u16,
+                data: &mut [u8],
+            ) -> Result<(), ::sondbus::slave::transceiver::MemoryError> {
+                <Self as ::sondbus::layout::MemoryLayout>::check_access(offset, data.len(), false)?;
+
+                let mut buf = [0u8; <Self as ::sondbus::layout::MemoryLayout>::SIZE];
+                ::sondbus::layout::MemoryLayout::store(self, &mut buf);
+
+                let start = offset as usize;
+                data.copy_from_slice(&buf[start..start + data.len()]);
+                Ok(())
+            }
+
+            fn write(
+                &mut self,
+                offset: u16,
+                data: &[u8],
+            ) -> Result<(), ::sondbus::slave::transceiver::MemoryError> {
+                <Self as ::sondbus::layout::MemoryLayout>::check_access(offset, data.len(), true)?;
+
+                let mut buf = [0u8; <Self as ::sondbus::layout::MemoryLayout>::SIZE];
+                ::sondbus::layout::MemoryLayout::store(self, &mut buf);
+
+                let start = offset as usize;
+                buf[start..start + data.len()].copy_from_slice(data);
+                ::sondbus::layout::MemoryLayout::load(self, &buf, offset, data.len());
+                Ok(())
+            }
+        }
+    })
+}
+
+fn parse_field(field: &syn::Field) -> Result<Field, Error> {
+    let mut result = Field {
+        ident: field.ident.clone().unwrap(),
+        ty: field.ty.clone(),
+        offset: None,
+        access: Access::ReadWrite,
+    };
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("sondbus")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("offset") {
+                result.offset = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("read_only") {
+                result.access = Access::ReadOnly;
+            } else if meta.path.is_ident("write_only") {
+                result.access = Access::WriteOnly;
+            } else {
+                return Err(meta.error("unknown sondbus attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(result)
+}
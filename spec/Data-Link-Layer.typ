@@ -21,6 +21,8 @@ This means that all devices need to take turns on communicating on the line and
 
 Sondbus can also work in a peer to peer manner in which it uses on-the-fly processing of frames or commands.
 In this mode, the master has a direct connection only to the first slave in the network and the following slaves are connected through each other.
+Every slave passes each octet on to the next slave, replacing it with its own data where it answers a read, and the last slave returns the frame to the master.
+This allows a single command to collect data from all slaves in one pass.
 
 == Command Structure
 
@@ -0,0 +1,553 @@
+//! A typed representation of the frames exchanged on the bus
+//! and a stateless encoder and decoder for them.
+//!
+//! In contrast to the [slave transceiver](crate::slave::transceiver::Transceiver),
+//! the decoder does not need a scratchpad or a callback and can be used
+//! for logging, sniffing the bus and for testing master implementations.
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    slave::transceiver::command::{AddressingMode, Command},
+    CMD_ASSIGN_LOGICAL_ADDRESS, CMD_NOP, CMD_POSITION_READ, CMD_POSITION_WRITE, CMD_RESET,
+    CMD_SEARCH, CMD_STATUS_READ, CMD_SYNC, START_BYTE, SYNC_SEQUENCE,
+};
+
+/// The address of the slave(s) a memory command is targeted at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlaveAddress {
+    /// No address, all slaves are targeted
+    Broadcast,
+
+    /// The slave is addressed by its unique physical (MAC) address
+    Physical([u8; 6]),
+
+    /// The slave is addressed by its logical address
+    Logical([u8; 2]),
+
+    /// No address, logical memory operation using the MMUs of the slaves
+    LogicalMemory,
+
+    /// The slave is addressed by its position in a chain of forwarding
+    /// slaves, starting at `0` for the slave next to the master
+    Position(u16),
+}
+
+impl SlaveAddress {
+    /// Returns the addressing mode bits as used in the command byte
+    fn mode(&self) -> u8 {
+        match self {
+            Self::Broadcast => 0,
+            Self::Physical(_) => 1,
+            Self::Logical(_) => 2,
+            Self::LogicalMemory => 3,
+            Self::Position(_) => 0,
+        }
+    }
+
+    /// Returns the address bytes that are sent over the bus
+    /// within a memory command, the position is sent before
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Self::Broadcast | Self::LogicalMemory | Self::Position(_) => &[],
+            Self::Physical(addr) => addr,
+            Self::Logical(addr) => addr,
+        }
+    }
+}
+
+/// A frame on the sondbus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame<'a> {
+    /// No operation
+    Nop,
+
+    /// Synchronize the slaves to the bus using the protocol `version`
+    Sync { version: u8 },
+
+    /// Reset the slaves to their boot state
+    Reset,
+
+    /// Assign the `logical` address to the slave with the `physical` address
+    AssignLogicalAddress { physical: [u8; 6], logical: [u8; 2] },
+
+    /// Search for slaves whose physical address starts with the
+    /// first `len` bits of `prefix`, which respond after the frame
+    Search { prefix: [u8; 6], len: u8 },
+
+    /// Read `size` bytes at `offset` from the slaves with the logical
+    /// addresses `first` to `first + slots - 1`, each one responding in
+    /// its own slot of `slot_time` microseconds after the frame
+    StatusRead {
+        first: [u8; 2],
+        slots: u8,
+        slot_time: u16,
+        offset: u16,
+        size: u8,
+    },
+
+    /// Read `size` bytes from memory at `offset`.
+    ///
+    /// The `payload` contains the data sent by the slave. It is empty
+    /// if only the request of the master is represented by this frame,
+    /// otherwise it has to be exactly `size` bytes long.
+    MemRead {
+        address: SlaveAddress,
+        offset: u16,
+        size: u16,
+        payload: &'a [u8],
+    },
+
+    /// Write the `payload` to memory at `offset`
+    MemWrite {
+        address: SlaveAddress,
+        offset: u16,
+        payload: &'a [u8],
+    },
+}
+
+/// A frame that has been decoded from a byte slice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded<'a> {
+    /// The sequence number of the frame
+    pub sequence: u8,
+
+    /// The decoded frame
+    pub frame: Frame<'a>,
+
+    /// The amount of bytes the frame occupied in the byte slice
+    pub len: usize,
+}
+
+/// The errors that can occur while encoding a frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The supplied buffer is too small to hold the frame
+    BufferTooSmall,
+
+    /// The payload does not fit into the size field of the frame
+    /// or does not match the size of a read
+    InvalidPayloadLength,
+}
+
+/// The errors that can occur while decoding a frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data ends before the frame is complete
+    Incomplete,
+
+    /// The data does not begin with the start byte
+    NoStartByte,
+
+    /// The command byte contains an unknown command
+    UnknownCommand(u8),
+
+    /// The magic sequence of a sync command is invalid
+    InvalidSyncMagic,
+
+    /// The header CRC of a read does not match
+    HeaderCRCMismatch { expected: u8, received: u8 },
+
+    /// The CRC at the end of the frame does not match
+    CRCMismatch { expected: u8, received: u8 },
+}
+
+impl Frame<'_> {
+    /// Returns the amount of bytes this frame occupies when it is encoded
+    ///
+    /// Fails for the same payloads as [Frame::encode()]
+    pub fn encoded_len(&self) -> Result<usize, EncodeError> {
+        // Start, command and CRC
+        let base = 3;
+
+        Ok(match self {
+            Self::Nop | Self::Reset => base,
+            Self::Sync { .. } => base + SYNC_SEQUENCE.len() + 1,
+            Self::AssignLogicalAddress { .. } => base + 8,
+            Self::Search { .. } => base + 7,
+            Self::StatusRead { .. } => base + 8,
+            Self::MemRead {
+                address,
+                offset,
+                size,
+                payload,
+            } => {
+                check_read_payload(*size, payload)?;
+                let response = if payload.is_empty() {
+                    0
+                } else {
+                    payload.len() + 1
+                };
+                base + mem_header_len(address, *offset, *size) + response
+            }
+            Self::MemWrite {
+                address,
+                offset,
+                payload,
+            } => base + mem_header_len(address, *offset, write_size(payload)?) + payload.len(),
+        })
+    }
+
+    /// Encodes the frame into `buf`.
+    ///
+    /// Reads with an empty payload are encoded up to and including
+    /// the header CRC, which is the part the master transmits.
+    /// Reads with a payload are encoded as a complete transaction.
+    /// # Arguments
+    /// * `sequence` - The sequence number to use for the frame
+    /// * `buf` - The buffer to encode the frame into
+    /// # Returns
+    /// The amount of bytes written to `buf`
+    pub fn encode(&self, sequence: u8, buf: &mut [u8]) -> Result<usize, EncodeError> {
+        let seq = (sequence & 0b11) << 6;
+        let mut w = Writer {
+            buf,
+            pos: 0,
+            crc: CRC8Autosar::new(),
+        };
+
+        w.push(START_BYTE)?;
+
+        match self {
+            Self::Nop => w.push(CMD_NOP | seq)?,
+            Self::Reset => w.push(CMD_RESET | seq)?,
+            Self::Sync { version } => {
+                w.push(CMD_SYNC | seq)?;
+                w.extend(&SYNC_SEQUENCE)?;
+                w.push(*version)?;
+            }
+            Self::AssignLogicalAddress { physical, logical } => {
+                w.push(CMD_ASSIGN_LOGICAL_ADDRESS | seq)?;
+                w.extend(physical)?;
+                w.extend(logical)?;
+            }
+            Self::Search { prefix, len } => {
+                w.push(CMD_SEARCH | seq)?;
+                w.push(*len)?;
+                w.extend(prefix)?;
+            }
+            Self::StatusRead {
+                first,
+                slots,
+                slot_time,
+                offset,
+                size,
+            } => {
+                w.push(CMD_STATUS_READ | seq)?;
+                w.extend(first)?;
+                w.push(*slots)?;
+                w.extend(&slot_time.to_le_bytes())?;
+                w.extend(&offset.to_le_bytes())?;
+                w.push(*size)?;
+            }
+            Self::MemRead {
+                address,
+                offset,
+                size,
+                payload,
+            } => {
+                check_read_payload(*size, payload)?;
+
+                w.mem_header(sequence, false, address, *offset, *size)?;
+
+                if !payload.is_empty() {
+                    w.push_crc()?;
+                    w.extend(payload)?;
+                }
+            }
+            Self::MemWrite {
+                address,
+                offset,
+                payload,
+            } => {
+                w.mem_header(sequence, true, address, *offset, write_size(payload)?)?;
+                w.extend(payload)?;
+            }
+        }
+
+        w.push_crc()?;
+        Ok(w.pos)
+    }
+
+    /// Decodes a frame from the beginning of `data`.
+    ///
+    /// A read that ends directly after the header CRC is decoded with an
+    /// empty payload, otherwise the response of the slave has to be complete.
+    /// # Arguments
+    /// * `data` - The data to decode the frame from
+    pub fn decode(data: &[u8]) -> Result<Decoded<'_>, DecodeError> {
+        let mut r = Reader {
+            data,
+            pos: 0,
+            crc: CRC8Autosar::new(),
+        };
+
+        if r.next()? != START_BYTE {
+            return Err(DecodeError::NoStartByte);
+        }
+
+        let cmd = Command::new(r.next()?);
+
+        // The address, whether it is a write and the lengths of
+        // the offset and size of memory commands
+        let mem = if cmd.is_mem_cmd() {
+            let address = match cmd.mem_slave_addressing_mode() {
+                AddressingMode::Broadcast => SlaveAddress::Broadcast,
+                AddressingMode::Physical => {
+                    let mut addr = [0u8; 6];
+                    addr.copy_from_slice(r.take(6)?);
+                    SlaveAddress::Physical(addr)
+                }
+                AddressingMode::Logical => {
+                    let mut addr = [0u8; 2];
+                    addr.copy_from_slice(r.take(2)?);
+                    SlaveAddress::Logical(addr)
+                }
+                AddressingMode::None => SlaveAddress::LogicalMemory,
+            };
+
+            Some((
+                address,
+                cmd.mem_is_write_cmd(),
+                cmd.mem_has_long_offset(),
+                cmd.mem_has_long_size(),
+            ))
+        } else if let CMD_POSITION_READ | CMD_POSITION_WRITE = cmd.without_sequence() {
+            let position = r.take(2)?;
+            let position = u16::from_le_bytes([position[0], position[1]]);
+            let write = cmd.without_sequence() == CMD_POSITION_WRITE;
+            Some((SlaveAddress::Position(position), write, true, true))
+        } else {
+            None
+        };
+
+        let frame = if let Some((address, write, long_offset, long_size)) = mem {
+            let offset = r.value(long_offset)?;
+            let size = r.value(long_size)?;
+
+            if write {
+                Frame::MemWrite {
+                    address,
+                    offset,
+                    payload: r.take(size as usize)?,
+                }
+            } else {
+                r.check_crc(true)?;
+
+                // Only the request has been transmitted
+                if r.pos == data.len() {
+                    return Ok(Decoded {
+                        sequence: cmd.sequence(),
+                        frame: Frame::MemRead {
+                            address,
+                            offset,
+                            size,
+                            payload: &[],
+                        },
+                        len: r.pos,
+                    });
+                }
+
+                Frame::MemRead {
+                    address,
+                    offset,
+                    size,
+                    payload: r.take(size as usize)?,
+                }
+            }
+        } else {
+            match cmd.without_sequence() {
+                CMD_NOP => Frame::Nop,
+                CMD_RESET => Frame::Reset,
+                CMD_SYNC => {
+                    if r.take(SYNC_SEQUENCE.len())? != SYNC_SEQUENCE {
+                        return Err(DecodeError::InvalidSyncMagic);
+                    }
+                    Frame::Sync { version: r.next()? }
+                }
+                CMD_ASSIGN_LOGICAL_ADDRESS => {
+                    let mut physical = [0u8; 6];
+                    physical.copy_from_slice(r.take(6)?);
+                    let mut logical = [0u8; 2];
+                    logical.copy_from_slice(r.take(2)?);
+                    Frame::AssignLogicalAddress { physical, logical }
+                }
+                CMD_SEARCH => {
+                    let len = r.next()?;
+                    let mut prefix = [0u8; 6];
+                    prefix.copy_from_slice(r.take(6)?);
+                    Frame::Search { prefix, len }
+                }
+                CMD_STATUS_READ => {
+                    let mut first = [0u8; 2];
+                    first.copy_from_slice(r.take(2)?);
+                    let slots = r.next()?;
+                    let slot_time = u16::from_le_bytes([r.next()?, r.next()?]);
+                    let offset = u16::from_le_bytes([r.next()?, r.next()?]);
+                    Frame::StatusRead {
+                        first,
+                        slots,
+                        slot_time,
+                        offset,
+                        size: r.next()?,
+                    }
+                }
+                other => return Err(DecodeError::UnknownCommand(other)),
+            }
+        };
+
+        r.check_crc(false)?;
+
+        Ok(Decoded {
+            sequence: cmd.sequence(),
+            frame,
+            len: r.pos,
+        })
+    }
+}
+
+/// Returns the length of the address, offset and size fields of a memory command
+fn mem_header_len(address: &SlaveAddress, offset: u16, size: u16) -> usize {
+    // Position addressed commands always use 16-bit offsets and sizes
+    if let SlaveAddress::Position(_) = address {
+        return 6;
+    }
+
+    address.bytes().len() + if offset > 0xFF { 2 } else { 1 } + if size > 0xFF { 2 } else { 1 }
+}
+
+/// Checks that the `payload` of a read is either empty or `size` bytes long
+fn check_read_payload(size: u16, payload: &[u8]) -> Result<(), EncodeError> {
+    if !payload.is_empty() && payload.len() != size as usize {
+        return Err(EncodeError::InvalidPayloadLength);
+    }
+    Ok(())
+}
+
+/// Returns the size field of a write carrying `payload`
+fn write_size(payload: &[u8]) -> Result<u16, EncodeError> {
+    u16::try_from(payload.len()).map_err(|_| EncodeError::InvalidPayloadLength)
+}
+
+struct Writer<'b> {
+    buf: &'b mut [u8],
+    pos: usize,
+    crc: CRC8Autosar,
+}
+
+impl Writer<'_> {
+    fn push(&mut self, v: u8) -> Result<(), EncodeError> {
+        let dst = self
+            .buf
+            .get_mut(self.pos)
+            .ok_or(EncodeError::BufferTooSmall)?;
+        *dst = v;
+        self.pos += 1;
+        self.crc.update_single(v);
+        Ok(())
+    }
+
+    fn extend(&mut self, data: &[u8]) -> Result<(), EncodeError> {
+        for d in data {
+            self.push(*d)?;
+        }
+        Ok(())
+    }
+
+    fn push_crc(&mut self) -> Result<(), EncodeError> {
+        self.push(self.crc.finalize())
+    }
+
+    fn mem_header(
+        &mut self,
+        sequence: u8,
+        write: bool,
+        address: &SlaveAddress,
+        offset: u16,
+        size: u16,
+    ) -> Result<(), EncodeError> {
+        let (long_offset, long_size) = if let SlaveAddress::Position(position) = address {
+            let cmd = if write {
+                CMD_POSITION_WRITE
+            } else {
+                CMD_POSITION_READ
+            };
+            self.push(cmd | (sequence & 0b11) << 6)?;
+            self.extend(&position.to_le_bytes())?;
+            (true, true)
+        } else {
+            let long_offset = offset > 0xFF;
+            let long_size = size > 0xFF;
+            self.push(
+                Command::new_mem(sequence, write, address.mode(), long_offset, long_size).raw(),
+            )?;
+            self.extend(address.bytes())?;
+            (long_offset, long_size)
+        };
+
+        // Long offsets and sizes are transmitted with the
+        // most significant byte first, as the slave expects them
+        if long_offset {
+            self.extend(&offset.to_be_bytes())?;
+        } else {
+            self.push(offset as u8)?;
+        }
+
+        if long_size {
+            self.extend(&size.to_be_bytes())
+        } else {
+            self.push(size as u8)
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    crc: CRC8Autosar,
+}
+
+impl<'a> Reader<'a> {
+    fn next(&mut self) -> Result<u8, DecodeError> {
+        let v = *self.data.get(self.pos).ok_or(DecodeError::Incomplete)?;
+        self.pos += 1;
+        self.crc.update_single(v);
+        Ok(v)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let data = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or(DecodeError::Incomplete)?;
+        self.pos += len;
+        self.crc.update(data);
+        Ok(data)
+    }
+
+    fn value(&mut self, long: bool) -> Result<u16, DecodeError> {
+        if long {
+            let v = self.take(2)?;
+            Ok(u16::from_be_bytes([v[0], v[1]]))
+        } else {
+            Ok(self.next()? as u16)
+        }
+    }
+
+    /// Checks the next byte against the CRC computed up to this point
+    /// # Arguments
+    /// * `header` - Whether the CRC is the header CRC of a read
+    fn check_crc(&mut self, header: bool) -> Result<(), DecodeError> {
+        let expected = self.crc.finalize();
+        let received = self.next()?;
+        if expected == received {
+            Ok(())
+        } else if header {
+            Err(DecodeError::HeaderCRCMismatch { expected, received })
+        } else {
+            Err(DecodeError::CRCMismatch { expected, received })
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    frame::{DecodeError, EncodeError, Frame, SlaveAddress},
+    CMD_POSITION_READ, START_BYTE,
+};
+
+/// Encode the frame, decode it again and make sure the result is the same
+fn roundtrip(frame: Frame, sequence: u8) {
+    let mut buf = [0u8; 64];
+    let len = frame.encode(sequence, &mut buf).unwrap();
+    assert_eq!(len, frame.encoded_len().unwrap(), "Encoded length mismatch");
+
+    let decoded = Frame::decode(&buf[..len]).unwrap();
+    assert_eq!(decoded.frame, frame);
+    assert_eq!(decoded.sequence, sequence);
+    assert_eq!(decoded.len, len);
+}
+
+#[test]
+fn roundtrip_management() {
+    roundtrip(Frame::Nop, 0);
+    roundtrip(Frame::Reset, 1);
+    roundtrip(Frame::Sync { version: 1 }, 2);
+    roundtrip(
+        Frame::AssignLogicalAddress {
+            physical: [1, 2, 3, 4, 5, 6],
+            logical: [7, 8],
+        },
+        3,
+    );
+    roundtrip(
+        Frame::Search {
+            prefix: [1, 2, 3, 0, 0, 0],
+            len: 20,
+        },
+        0,
+    );
+    roundtrip(
+        Frame::StatusRead {
+            first: [1, 0],
+            slots: 8,
+            slot_time: 0x1234,
+            offset: 0x0102,
+            size: 4,
+        },
+        1,
+    );
+}
+
+#[test]
+fn roundtrip_mem() {
+    let addresses = [
+        SlaveAddress::Broadcast,
+        SlaveAddress::Physical([1, 2, 3, 4, 5, 6]),
+        SlaveAddress::Logical([5, 6]),
+        SlaveAddress::LogicalMemory,
+        SlaveAddress::Position(0x1234),
+    ];
+
+    for address in addresses {
+        for offset in [0, 0x12, 0x1234] {
+            roundtrip(
+                Frame::MemWrite {
+                    address: address.clone(),
+                    offset,
+                    payload: &[0xAA, 0xBB],
+                },
+                3,
+            );
+            roundtrip(
+                Frame::MemRead {
+                    address: address.clone(),
+                    offset,
+                    size: 0x100,
+                    payload: &[],
+                },
+                3,
+            );
+            roundtrip(
+                Frame::MemRead {
+                    address: address.clone(),
+                    offset,
+                    size: 2,
+                    payload: &[0xCC, 0xDD],
+                },
+                3,
+            );
+        }
+    }
+}
+
+#[test]
+#[allow(clippy::identity_op)]
+fn decode_matches_slave_layout() {
+    let cmd_byte = 0
+        | 1 << 5 // Perform a memory command
+        | 1 << 3 // Long offset
+        | 1 << 0; // Operation: Write
+
+    let mut data = vec![START_BYTE, cmd_byte, 0x12, 0x34, 1, 0xAA];
+    data.push(CRC8Autosar::new().update_move(&data).finalize());
+
+    let decoded = Frame::decode(&data).unwrap();
+    assert_eq!(
+        decoded.frame,
+        Frame::MemWrite {
+            address: SlaveAddress::Broadcast,
+            offset: 0x1234,
+            payload: &[0xAA]
+        }
+    );
+}
+
+#[test]
+fn decode_position_layout() {
+    // The position is little-endian, followed by a 16-bit offset and size
+    let mut data = vec![START_BYTE, CMD_POSITION_READ | 1 << 6, 0x02, 0x01];
+    data.extend_from_slice(&[0x00, 0x10, 0x00, 0x04]);
+    data.push(CRC8Autosar::new().update_move(&data).finalize());
+
+    let decoded = Frame::decode(&data).unwrap();
+    assert_eq!(decoded.sequence, 1);
+    assert_eq!(
+        decoded.frame,
+        Frame::MemRead {
+            address: SlaveAddress::Position(0x0102),
+            offset: 0x10,
+            size: 4,
+            payload: &[]
+        }
+    );
+}
+
+#[test]
+fn decode_errors() {
+    assert_eq!(Frame::decode(&[]), Err(DecodeError::Incomplete));
+    assert_eq!(Frame::decode(&[0x00]), Err(DecodeError::NoStartByte));
+    assert_eq!(
+        Frame::decode(&[START_BYTE, 0x1F]),
+        Err(DecodeError::UnknownCommand(0x1F))
+    );
+
+    let mut buf = [0u8; 32];
+    let len = Frame::Nop.encode(0, &mut buf).unwrap();
+    buf[len - 1] ^= 0xFF;
+    assert!(matches!(
+        Frame::decode(&buf[..len]),
+        Err(DecodeError::CRCMismatch { .. })
+    ));
+
+    let frame = Frame::MemRead {
+        address: SlaveAddress::Broadcast,
+        offset: 0,
+        size: 1,
+        payload: &[],
+    };
+    let len = frame.encode(0, &mut buf).unwrap();
+    buf[len - 1] ^= 0xFF;
+    assert!(matches!(
+        Frame::decode(&buf[..len]),
+        Err(DecodeError::HeaderCRCMismatch { .. })
+    ));
+
+    // A read with an incomplete response
+    let frame = Frame::MemRead {
+        address: SlaveAddress::Broadcast,
+        offset: 0,
+        size: 2,
+        payload: &[1, 2],
+    };
+    let len = frame.encode(0, &mut buf).unwrap();
+    assert_eq!(Frame::decode(&buf[..len - 2]), Err(DecodeError::Incomplete));
+}
+
+#[test]
+fn encode_errors() {
+    let mut buf = [0u8; 4];
+    assert_eq!(
+        Frame::Sync { version: 1 }.encode(0, &mut buf),
+        Err(EncodeError::BufferTooSmall)
+    );
+
+    let frame = Frame::MemRead {
+        address: SlaveAddress::Broadcast,
+        offset: 0,
+        size: 2,
+        payload: &[1],
+    };
+    assert_eq!(
+        frame.encode(0, &mut [0u8; 16]),
+        Err(EncodeError::InvalidPayloadLength)
+    );
+    assert_eq!(frame.encoded_len(), Err(EncodeError::InvalidPayloadLength));
+
+    // The size field of a write is limited to 16 bits
+    let frame = Frame::MemWrite {
+        address: SlaveAddress::Broadcast,
+        offset: 0,
+        payload: &[0u8; 0x10000],
+    };
+    assert_eq!(frame.encoded_len(), Err(EncodeError::InvalidPayloadLength));
+    assert_eq!(
+        frame.encode(0, &mut [0u8; 0x10010]),
+        Err(EncodeError::InvalidPayloadLength)
+    );
+}
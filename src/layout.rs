@@ -0,0 +1,190 @@
+//! Typed layouts of the memory area of a slave.
+//!
+//! A layout describes the fields in the memory area of a slave, their
+//! offsets and the accesses allowed to them. It is usually generated using
+//! the `SondbusMemory` derive macro (feature `derive`), which
+//! lays out the fields of a struct one after the other in declaration order,
+//! unless an explicit `#[sondbus(offset = ...)]` is given. Fields can be
+//! restricted using `#[sondbus(read_only)]` or `#[sondbus(write_only)]`.
+//!
+//! The slave uses the struct itself as its [SlaveMemory](crate::slave::transceiver::SlaveMemory),
+//! while the master uses the generated [Field] constants to access the
+//! fields without duplicating any offsets. All values are little-endian.
+
+#[cfg(test)]
+mod test;
+
+use core::marker::PhantomData;
+
+pub use crate::slave::memory_map::Access;
+use crate::slave::transceiver::MemoryError;
+
+#[cfg(feature = "derive")]
+pub use sondbus_derive::SondbusMemory;
+
+/// A value that can be stored in a field of a layout
+pub trait FieldValue: Sized {
+    /// The amount of bytes the value occupies in memory
+    const SIZE: usize;
+
+    /// Serializes the value into `buf`, which is exactly [Self::SIZE] bytes long
+    fn to_bytes(&self, buf: &mut [u8]);
+
+    /// Deserializes the value from `buf`, which is exactly [Self::SIZE] bytes long
+    fn from_bytes(buf: &[u8]) -> Self;
+}
+
+macro_rules! impl_field_value {
+    ($($t:ty),*) => {
+        $(
+            impl FieldValue for $t {
+                const SIZE: usize = core::mem::size_of::<$t>();
+
+                fn to_bytes(&self, buf: &mut [u8]) {
+                    buf.copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn from_bytes(buf: &[u8]) -> Self {
+                    let mut bytes = [0u8; core::mem::size_of::<$t>()];
+                    bytes.copy_from_slice(buf);
+                    Self::from_le_bytes(bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_field_value!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+
+impl FieldValue for bool {
+    const SIZE: usize = 1;
+
+    fn to_bytes(&self, buf: &mut [u8]) {
+        buf[0] = *self as u8;
+    }
+
+    fn from_bytes(buf: &[u8]) -> Self {
+        buf[0] != 0
+    }
+}
+
+impl<T: FieldValue, const N: usize> FieldValue for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+
+    fn to_bytes(&self, buf: &mut [u8]) {
+        for (v, chunk) in self.iter().zip(buf.chunks_mut(T::SIZE)) {
+            v.to_bytes(chunk);
+        }
+    }
+
+    fn from_bytes(buf: &[u8]) -> Self {
+        core::array::from_fn(|i| T::from_bytes(&buf[i * T::SIZE..(i + 1) * T::SIZE]))
+    }
+}
+
+/// A typed field in the memory area of a slave
+#[derive(Debug, PartialEq, Eq)]
+pub struct Field<T> {
+    /// The offset of the field in the memory area
+    pub offset: u16,
+
+    /// The accesses that are allowed to the field
+    pub access: Access,
+
+    value: PhantomData<T>,
+}
+
+impl<T> Clone for Field<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Field<T> {}
+
+impl<T: FieldValue> Field<T> {
+    /// Creates a new field
+    /// # Arguments
+    /// * `offset` - The offset of the field in the memory area
+    /// * `access` - The accesses that are allowed to the field
+    pub const fn new(offset: u16, access: Access) -> Self {
+        Self {
+            offset,
+            access,
+            value: PhantomData,
+        }
+    }
+
+    /// Returns the size of the field in bytes
+    pub const fn size(&self) -> u16 {
+        T::SIZE as u16
+    }
+}
+
+/// The untyped description of a field in a [MemoryLayout]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldInfo {
+    /// The offset of the field in the memory area
+    pub offset: u16,
+
+    /// The size of the field in bytes
+    pub size: u16,
+
+    /// The accesses that are allowed to the field
+    pub access: Access,
+}
+
+impl FieldInfo {
+    fn end(&self) -> u32 {
+        self.offset as u32 + self.size as u32
+    }
+}
+
+/// A type describing the layout of the memory area of a slave
+pub trait MemoryLayout {
+    /// The size of the memory area covered by the layout
+    const SIZE: usize;
+
+    /// The fields of the layout, ordered by their offset
+    const FIELDS: &'static [FieldInfo];
+
+    /// Serializes all fields into `buf`, which is [Self::SIZE] bytes long
+    fn store(&self, buf: &mut [u8]);
+
+    /// Deserializes the fields touched by the range of `len` bytes
+    /// at `offset` from `buf`, which is [Self::SIZE] bytes long
+    fn load(&mut self, buf: &[u8], offset: u16, len: usize);
+
+    /// Checks that the access of `len` bytes at `offset` is
+    /// completely covered by fields that allow it
+    fn check_access(offset: u16, len: usize, write: bool) -> Result<(), MemoryError> {
+        let end = offset as u32 + len as u32;
+        let mut pos = offset as u32;
+
+        for field in Self::FIELDS {
+            if pos >= end {
+                break;
+            }
+
+            if field.end() <= pos {
+                continue;
+            }
+
+            if field.offset as u32 > pos {
+                return Err(MemoryError::OutOfRange);
+            }
+
+            if !field.access.allows(write) {
+                return Err(MemoryError::AccessDenied);
+            }
+
+            pos = field.end();
+        }
+
+        if pos < end {
+            Err(MemoryError::OutOfRange)
+        } else {
+            Ok(())
+        }
+    }
+}
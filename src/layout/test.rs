@@ -0,0 +1,76 @@
+use sondbus_derive::SondbusMemory;
+
+use crate::{
+    layout::{Access, FieldInfo, FieldValue, MemoryLayout},
+    slave::transceiver::{MemoryError, SlaveMemory},
+};
+
+#[derive(SondbusMemory, Default)]
+struct Memory {
+    #[sondbus(read_only)]
+    version: u16,
+    setpoint: i32,
+    #[sondbus(write_only)]
+    command: u8,
+    #[sondbus(offset = 0x10)]
+    flags: [bool; 2],
+}
+
+#[test]
+fn offsets() {
+    assert_eq!(Memory::VERSION.offset, 0);
+    assert_eq!(Memory::SETPOINT.offset, 2);
+    assert_eq!(Memory::COMMAND.offset, 6);
+    assert_eq!(Memory::FLAGS.offset, 0x10);
+    assert_eq!(Memory::FLAGS.size(), 2);
+    assert_eq!(<Memory as MemoryLayout>::SIZE, 0x12);
+    assert_eq!(
+        Memory::FIELDS[0],
+        FieldInfo {
+            offset: 0,
+            size: 2,
+            access: Access::ReadOnly
+        }
+    );
+}
+
+#[test]
+fn read_write() {
+    let mut m = Memory {
+        version: 0x0102,
+        ..Default::default()
+    };
+
+    m.write(2, &(-2i32).to_le_bytes()).unwrap();
+    assert_eq!(m.setpoint, -2);
+
+    // Partial writes only change the touched bytes
+    m.write(3, &[0x00]).unwrap();
+    assert_eq!(m.setpoint, -2 & !0xFF00);
+
+    let mut data = [0u8; 6];
+    m.read(0, &mut data).unwrap();
+    assert_eq!(&data[0..2], &[0x02, 0x01]);
+    assert_eq!(i32::from_bytes(&data[2..6]), m.setpoint);
+
+    m.write(0x10, &[0, 1]).unwrap();
+    assert_eq!(m.flags, [false, true]);
+}
+
+#[test]
+fn access_rules() {
+    let mut m = Memory::default();
+    let mut data = [0u8; 4];
+
+    assert_eq!(m.write(0, &[1, 2]), Err(MemoryError::AccessDenied));
+    assert_eq!(m.read(5, &mut data[..2]), Err(MemoryError::AccessDenied));
+
+    // The gap between `command` and `flags` is not mapped
+    assert_eq!(m.read(7, &mut data[..1]), Err(MemoryError::OutOfRange));
+    assert_eq!(m.read(0x11, &mut data[..2]), Err(MemoryError::OutOfRange));
+
+    // Rejected accesses never touch the memory
+    assert_eq!(m.write(5, &[1, 2, 3]), Err(MemoryError::OutOfRange));
+    assert_eq!(m.setpoint, 0);
+    assert_eq!(m.command, 0);
+}
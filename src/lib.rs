@@ -1,7 +1,17 @@
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+// Allows the derive macros to refer to `::sondbus` within this crate
+extern crate self as sondbus;
 
 pub mod crc8;
+pub mod frame;
+pub mod layout;
+#[cfg(feature = "master")]
+pub mod master;
+#[cfg(feature = "sim")]
+pub mod sim;
 pub mod slave;
+pub mod system;
 
 /// The byte sequence of the `SYNC` command
 pub const SYNC_SEQUENCE: [u8; 15] = [
@@ -11,6 +21,14 @@ pub const SYNC_SEQUENCE: [u8; 15] = [
 pub const START_BYTE: u8 = 0x55;
 pub const CMD_NOP: u8 = 0x00;
 pub const CMD_SYNC: u8 = 0x01;
+pub const CMD_RESET: u8 = 0x02;
+pub const CMD_ASSIGN_LOGICAL_ADDRESS: u8 = 0x03;
+pub const CMD_POSITION_READ: u8 = 0x04;
+pub const CMD_POSITION_WRITE: u8 = 0x05;
+pub const CMD_SEARCH: u8 = 0x06;
+pub const CMD_STATUS_READ: u8 = 0x07;
+/// The byte slaves respond with to a matching `SEARCH` command
+pub const SEARCH_RESPONSE: u8 = 0xAA;
 pub const PROTOCOL_VERSION_1: u8 = 0x01;
 
 macro_rules! test_log{
@@ -0,0 +1,404 @@
+//! The implementation of a master in the sondbus system
+
+pub mod transceiver;
+pub mod transport;
+
+use std::time::Duration;
+
+use crate::{
+    frame::{Frame, SlaveAddress},
+    layout::{Field, FieldValue},
+    master::{
+        transceiver::{Response, Transceiver},
+        transport::{Transport, TransportError},
+    },
+    system::{
+        Diagnostics, MmuEntry, SlaveCapabilities, CAPABILITIES_OFFSET, CAPABILITIES_SIZE,
+        DIAGNOSTICS_OFFSET, DIAGNOSTICS_SIZE, MMU_COUNT, MMU_ENTRY_SIZE, MMU_OFFSET,
+        WATCHDOG_OFFSET, WORKING_COUNTER_OFFSET,
+    },
+    PROTOCOL_VERSION_1,
+};
+
+/// The status block of a slave returned by a [status read](Master::read_status)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Status {
+    /// The logical address of the slave
+    pub logical: [u8; 2],
+
+    /// The status block read from the memory of the slave
+    pub data: Vec<u8>,
+}
+
+/// A master that issues commands on the bus using a [Transport]
+pub struct Master<T: Transport> {
+    transport: T,
+    transceiver: Transceiver,
+}
+
+impl<T: Transport> Master<T> {
+    /// Creates a new master
+    /// # Arguments
+    /// * `transport` - The transport to use for communicating with the slaves
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            transceiver: Transceiver::new(),
+        }
+    }
+
+    /// Returns the underlying transport
+    pub fn transport(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Transfers a frame and waits for the response
+    /// # Arguments
+    /// * `frame` - The frame to transfer
+    pub fn transfer(&mut self, frame: &Frame) -> Result<Response, TransportError> {
+        self.transport.transfer(&mut self.transceiver, frame)
+    }
+
+    /// Synchronizes all slaves to the bus
+    pub fn sync(&mut self) -> Result<(), TransportError> {
+        self.transfer(&Frame::Sync {
+            version: PROTOCOL_VERSION_1,
+        })
+        .map(|_| ())
+    }
+
+    /// Resets all slaves to their boot state, which disables the working counter
+    pub fn reset(&mut self) -> Result<(), TransportError> {
+        self.transfer(&Frame::Reset)?;
+        self.transceiver.set_ack_slots(0);
+        Ok(())
+    }
+
+    /// Assigns a logical address to a slave, allowing it to be addressed
+    /// by the compact logical address instead of its physical address
+    /// # Arguments
+    /// * `physical` - The physical address of the slave
+    /// * `logical` - The logical address to assign
+    pub fn assign_logical_address(
+        &mut self,
+        physical: [u8; 6],
+        logical: [u8; 2],
+    ) -> Result<(), TransportError> {
+        self.transfer(&Frame::AssignLogicalAddress { physical, logical })
+            .map(|_| ())
+    }
+
+    /// Searches for slaves whose physical address starts with a prefix.
+    ///
+    /// All matching slaves respond at once, so only their presence
+    /// is detected, no matter if their responses collide.
+    /// # Arguments
+    /// * `prefix` - The prefix of the physical address
+    /// * `len` - The length of the prefix in bits, up to `48`
+    /// # Returns
+    /// Whether any slave matches the prefix
+    pub fn search(&mut self, prefix: [u8; 6], len: u8) -> Result<bool, TransportError> {
+        match self.transfer(&Frame::Search { prefix, len }) {
+            // In a chain of slaves, the empty response returns
+            Ok(Response::Data(data)) => Ok(data != [0]),
+            Ok(Response::None) => Ok(true),
+            Err(TransportError::Timeout) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Enumerates the physical addresses of all slaves that are in sync.
+    ///
+    /// This walks the address space by a binary [search()](Self::search),
+    /// following only the prefixes some slave responds to. Every present
+    /// slave takes up to 96 searches, every absent prefix a timeout.
+    /// # Returns
+    /// The physical addresses of the slaves in ascending order
+    pub fn scan(&mut self) -> Result<Vec<[u8; 6]>, TransportError> {
+        let mut found = Vec::new();
+        let mut pending = vec![([0u8; 6], 0u8)];
+
+        while let Some((prefix, len)) = pending.pop() {
+            if !self.search(prefix, len)? {
+                continue;
+            }
+
+            if len as usize == prefix.len() * 8 {
+                found.push(prefix);
+                continue;
+            }
+
+            // Extend the prefix by a `1` and a `0` bit,
+            // searching the `0` branch first
+            let mut one = prefix;
+            one[len as usize / 8] |= 0x80 >> (len % 8);
+            pending.push((one, len + 1));
+            pending.push((prefix, len + 1));
+        }
+
+        Ok(found)
+    }
+
+    /// Reads a status block from a range of slaves using a single frame.
+    ///
+    /// Every slave with a logical address in the range responds in its own
+    /// slot, which starts `slot_time` after the slot of the previous logical
+    /// address. The slot time needs to cover the response of `size + 3`
+    /// bytes and a gap for the turnaround of the bus, in which no slave
+    /// transmits. Only slaves on a shared medium that are
+    /// [ticked](crate::slave::transceiver::Transceiver::tick) and have
+    /// [status reads enabled](crate::slave::transceiver::Transceiver::set_status_read)
+    /// respond.
+    /// # Arguments
+    /// * `first` - The logical address of the slave in the first slot
+    /// * `slots` - The amount of slots, one for each logical address
+    /// * `slot_time` - The time every slot takes, limited to `u16::MAX` microseconds
+    /// * `offset` - The offset of the status block in the memory of the slaves
+    /// * `size` - The size of the status block
+    /// # Returns
+    /// The status blocks of the slaves that responded
+    pub fn read_status(
+        &mut self,
+        first: [u8; 2],
+        slots: u8,
+        slot_time: Duration,
+        offset: u16,
+        size: u8,
+    ) -> Result<Vec<Status>, TransportError> {
+        let frame = Frame::StatusRead {
+            first,
+            slots,
+            slot_time: slot_time.as_micros().min(u16::MAX as u128) as u16,
+            offset,
+            size,
+        };
+
+        let data = match self.transfer(&frame)? {
+            Response::Data(data) => data,
+            Response::None => Vec::new(),
+        };
+
+        Ok(data
+            .chunks(size as usize + 2)
+            .map(|r| Status {
+                logical: [r[0], r[1]],
+                data: r[2..].to_vec(),
+            })
+            .collect())
+    }
+
+    /// Reads the capability descriptor of a slave to discover
+    /// the optional features it supports
+    /// # Arguments
+    /// * `address` - The address of the slave to query
+    pub fn read_capabilities(
+        &mut self,
+        address: SlaveAddress,
+    ) -> Result<SlaveCapabilities, TransportError> {
+        let data = self.read(address, CAPABILITIES_OFFSET, CAPABILITIES_SIZE as u16)?;
+        SlaveCapabilities::parse(&data).ok_or(TransportError::Protocol(
+            transceiver::Error::InvalidResponse,
+        ))
+    }
+
+    /// Reads the diagnostics counters of a slave to judge the health of its link
+    /// # Arguments
+    /// * `address` - The address of the slave to query
+    pub fn read_diagnostics(
+        &mut self,
+        address: SlaveAddress,
+    ) -> Result<Diagnostics, TransportError> {
+        let data = self.read(address, DIAGNOSTICS_OFFSET, DIAGNOSTICS_SIZE as u16)?;
+        Diagnostics::parse(&data).ok_or(TransportError::Protocol(
+            transceiver::Error::InvalidResponse,
+        ))
+    }
+
+    /// Configures an entry of the MMU of a slave, mapping a window of the
+    /// logical address space used by [logical memory](SlaveAddress::LogicalMemory)
+    /// operations onto the memory area of the slave
+    /// # Arguments
+    /// * `address` - The address of the slave to configure
+    /// * `index` - The index of the MMU entry, below [MMU_COUNT]
+    /// * `entry` - The new entry or `None` to disable it
+    pub fn configure_mmu(
+        &mut self,
+        address: SlaveAddress,
+        index: usize,
+        entry: Option<MmuEntry>,
+    ) -> Result<(), TransportError> {
+        if index >= MMU_COUNT {
+            return Err(TransportError::Protocol(transceiver::Error::InvalidIndex));
+        }
+
+        let offset = MMU_OFFSET + (index * MMU_ENTRY_SIZE) as u16;
+        self.write(address, offset, &MmuEntry::to_bytes(entry.as_ref()))
+    }
+
+    /// Configures the communication watchdog of a slave. The slave drops sync
+    /// and enters its safe state if it does not receive a valid frame within
+    /// the `timeout`, so the master has to keep the bus busy
+    /// # Arguments
+    /// * `address` - The address of the slave to configure
+    /// * `timeout` - The timeout with millisecond resolution, limited to
+    ///   `u16::MAX` milliseconds, or `None` to disable the watchdog
+    pub fn configure_watchdog(
+        &mut self,
+        address: SlaveAddress,
+        timeout: Option<Duration>,
+    ) -> Result<(), TransportError> {
+        let ms = match timeout {
+            Some(timeout) => timeout.as_millis().clamp(1, u16::MAX as u128) as u16,
+            None => 0,
+        };
+
+        self.write(address, WATCHDOG_OFFSET, &ms.to_le_bytes())
+    }
+
+    /// Reads `size` bytes from the memory of a slave.
+    ///
+    /// Broadcast reads are only supported by transports to chains of
+    /// forwarding slaves and return the bitwise OR of the data of all
+    /// slaves. On a shared medium, the responses would collide.
+    /// # Arguments
+    /// * `address` - The address of the slave to read from
+    /// * `offset` - The offset to read from
+    /// * `size` - The amount of bytes to read
+    pub fn read(
+        &mut self,
+        address: SlaveAddress,
+        offset: u16,
+        size: u16,
+    ) -> Result<Vec<u8>, TransportError> {
+        let res = self.transfer(&Frame::MemRead {
+            address,
+            offset,
+            size,
+            payload: &[],
+        })?;
+
+        match res {
+            Response::Data(data) => Ok(data),
+            Response::None => Ok(Vec::new()),
+        }
+    }
+
+    /// Reads a typed field from the memory of a slave
+    /// # Arguments
+    /// * `address` - The address of the slave to read from
+    /// * `field` - The field to read
+    pub fn read_field<V: FieldValue>(
+        &mut self,
+        address: SlaveAddress,
+        field: Field<V>,
+    ) -> Result<V, TransportError> {
+        let data = self.read(address, field.offset, field.size())?;
+        if data.len() != V::SIZE {
+            return Err(TransportError::Protocol(
+                transceiver::Error::InvalidResponse,
+            ));
+        }
+
+        Ok(V::from_bytes(&data))
+    }
+
+    /// Writes a typed field to the memory of a slave
+    /// # Arguments
+    /// * `address` - The address of the slave to write to
+    /// * `field` - The field to write
+    /// * `value` - The value to write
+    pub fn write_field<V: FieldValue>(
+        &mut self,
+        address: SlaveAddress,
+        field: Field<V>,
+        value: V,
+    ) -> Result<(), TransportError> {
+        let mut data = vec![0u8; V::SIZE];
+        value.to_bytes(&mut data);
+        self.write(address, field.offset, &data)
+    }
+
+    /// Writes `data` to the memory of a slave
+    /// # Arguments
+    /// * `address` - The address of the slave to write to
+    /// * `offset` - The offset to write to
+    /// * `data` - The data to write
+    pub fn write(
+        &mut self,
+        address: SlaveAddress,
+        offset: u16,
+        data: &[u8],
+    ) -> Result<(), TransportError> {
+        self.transfer(&Frame::MemWrite {
+            address,
+            offset,
+            payload: data,
+        })
+        .map(|_| ())
+    }
+
+    /// Writes `data` to the memory of slaves and makes sure
+    /// that exactly `expected` slaves applied the write
+    /// # Arguments
+    /// * `address` - The address of the slaves to write to
+    /// * `offset` - The offset to write to
+    /// * `data` - The data to write
+    /// * `expected` - The expected working counter
+    pub fn write_acknowledged(
+        &mut self,
+        address: SlaveAddress,
+        offset: u16,
+        data: &[u8],
+        expected: u16,
+    ) -> Result<(), TransportError> {
+        if self.transceiver.ack_slots() == 0 {
+            return Err(TransportError::Protocol(
+                transceiver::Error::WorkingCounterDisabled,
+            ));
+        }
+
+        self.write(address, offset, data)?;
+
+        let received = self.working_counter().unwrap_or(0);
+        if received != expected {
+            return Err(TransportError::Protocol(
+                transceiver::Error::WorkingCounter { expected, received },
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Enables the working counter on all slaves, which acknowledge every
+    /// memory command they applied in one of the `slots` following it.
+    ///
+    /// In a chain of forwarding slaves, every slave increments its slot as
+    /// it passes, so a single slot counts all slaves. On a shared medium,
+    /// every slave acknowledges in a slot of its own, which have to be
+    /// assigned using [assign_ack_slot()](Self::assign_ack_slot) before.
+    /// # Arguments
+    /// * `slots` - The amount of ack slots, `0` disabling the working counter
+    pub fn configure_working_counter(&mut self, slots: u8) -> Result<(), TransportError> {
+        self.write(SlaveAddress::Broadcast, WORKING_COUNTER_OFFSET, &[slots])?;
+        self.transceiver.set_ack_slots(slots);
+        Ok(())
+    }
+
+    /// Assigns the ack slot a slave acknowledges memory commands in
+    /// # Arguments
+    /// * `address` - The address of the slave to configure
+    /// * `slot` - The index of the slot
+    pub fn assign_ack_slot(
+        &mut self,
+        address: SlaveAddress,
+        slot: u8,
+    ) -> Result<(), TransportError> {
+        self.write(address, WORKING_COUNTER_OFFSET + 1, &[slot])
+    }
+
+    /// Returns the working counter of the last memory command, counting the
+    /// slaves that applied it, or `None` if the working counter is disabled
+    pub fn working_counter(&self) -> Option<u16> {
+        self.transceiver.working_counter()
+    }
+}
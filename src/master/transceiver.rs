@@ -0,0 +1,502 @@
+//! The master transceiver implements the master side of the lowest layer
+//! of the sondbus communication protocol. It encodes the commands issued
+//! by the master and consumes the responses of the slaves.
+
+#[cfg(test)]
+mod test;
+
+use core::time::Duration;
+
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    frame::{EncodeError, Frame, SlaveAddress},
+    test_log,
+};
+
+/// The response to a finished request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    /// The request has been transmitted, no data is returned
+    None,
+
+    /// The data that has been read from the slave or, for a
+    /// search, the response of the matching slaves. For a status
+    /// read, these are the logical addresses and the data of the
+    /// slaves that responded, without their CRCs.
+    Data(Vec<u8>),
+}
+
+/// The errors that can occur while processing a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A request is already in progress
+    Busy,
+
+    /// The frame could not be encoded
+    Encode(EncodeError),
+
+    /// The CRC received from the slave does not match the
+    /// CRC computed over the frame
+    CRCMismatch { expected: u8, received: u8 },
+
+    /// The request has been aborted before it was finished
+    Aborted,
+
+    /// The data returned by the slave could not be interpreted
+    InvalidResponse,
+
+    /// The frame cannot be transferred on the medium, like broadcast
+    /// reads and position addressed commands, which only slaves in
+    /// forwarding mode support, or status reads, which only slaves
+    /// on a shared medium answer
+    Unsupported,
+
+    /// Fewer or more slaves than expected applied the command
+    WorkingCounter { expected: u16, received: u16 },
+
+    /// The command needs the working counter, which is not enabled
+    WorkingCounterDisabled,
+
+    /// The index of a table entry of the slaves is out of range
+    InvalidIndex,
+}
+
+/// Enumerates the possible states the [Transceiver] can be in
+#[derive(Clone, PartialEq, Debug)]
+enum State {
+    Idle,
+    Transmit,
+    RxEcho,
+    RxPayload,
+    RxCRC,
+    RxAckSlots,
+    RxSearchResponse,
+    RxStatus,
+}
+
+/// Represents a master transceiver in the sondbus model.
+///
+/// The transceiver is fed by the physical layer in the same manner
+/// as the [slave transceiver](crate::slave::transceiver::Transceiver):
+/// [handle()](Self::handle) is called with incoming bytes and returns
+/// the bytes to send.
+pub struct Transceiver {
+    state: State,
+    crc: CRC8Autosar,
+
+    sequence_no: u8,
+
+    tx: Vec<u8>,
+    pos: usize,
+
+    rx: Vec<u8>,
+    rx_size: usize,
+    reading: bool,
+    searching: bool,
+
+    /// Whether the slaves process the frames on the fly and return them
+    forwarding: bool,
+    /// The amount of bytes of the returning frame to skip
+    echo: usize,
+
+    /// The amount of slots of the current status read and their duration
+    status_slots: usize,
+    slot_time: Duration,
+
+    /// The amount of ack slots following every memory command
+    ack_slots: u8,
+    /// The amount of ack slots left to receive for the current request
+    acks: usize,
+    working_counter: Option<u16>,
+
+    result: Option<Result<Response, Error>>,
+}
+
+impl Default for Transceiver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transceiver {
+    /// Creates a new master transceiver
+    pub fn new() -> Self {
+        Self {
+            state: State::Idle,
+            crc: CRC8Autosar::new_const(),
+            sequence_no: 0,
+            tx: Vec::new(),
+            pos: 0,
+            rx: Vec::new(),
+            rx_size: 0,
+            reading: false,
+            searching: false,
+            forwarding: false,
+            echo: 0,
+            status_slots: 0,
+            slot_time: Duration::ZERO,
+            ack_slots: 0,
+            acks: 0,
+            working_counter: None,
+            result: None,
+        }
+    }
+
+    /// Returns the sequence number of the last issued command
+    pub fn sequence_no(&self) -> u8 {
+        self.sequence_no
+    }
+
+    /// Configures the transceiver for slaves that process the frames
+    /// on the fly and return them to the master, as in a chain of slaves.
+    ///
+    /// In this mode, reads are transmitted with placeholders for the
+    /// payload and the CRC, which are filled in by the slaves. The frame
+    /// returns to the master, which skips the header it sent itself.
+    /// Broadcast reads are only supported in this mode, returning the
+    /// bitwise OR of the data of all slaves, as the slaves combine their
+    /// data as it passes. Reads no slave answers return the placeholders
+    /// and searches no slave matches return an empty response of `0`.
+    /// # Arguments
+    /// * `forwarding` - Whether to operate in forwarding mode
+    pub fn set_forwarding(&mut self, forwarding: bool) {
+        self.forwarding = forwarding;
+    }
+
+    /// Sets the amount of ack slots the slaves append to every memory
+    /// command, which need to match the configuration of the slaves
+    /// # Arguments
+    /// * `slots` - The amount of ack slots, `0` disabling the working counter
+    pub fn set_ack_slots(&mut self, slots: u8) {
+        self.ack_slots = slots;
+    }
+
+    /// Returns the amount of ack slots following every memory command
+    pub fn ack_slots(&self) -> u8 {
+        self.ack_slots
+    }
+
+    /// Returns the working counter of the last memory command, which is the
+    /// sum of its ack slots, or `None` if the working counter is disabled
+    pub fn working_counter(&self) -> Option<u16> {
+        self.working_counter
+    }
+
+    /// Returns whether a request is currently in progress
+    pub fn is_busy(&self) -> bool {
+        self.state != State::Idle
+    }
+
+    /// Returns whether the transceiver waits for data from a slave
+    pub fn is_receiving(&self) -> bool {
+        matches!(
+            self.state,
+            State::RxEcho
+                | State::RxPayload
+                | State::RxCRC
+                | State::RxAckSlots
+                | State::RxSearchResponse
+                | State::RxStatus
+        )
+    }
+
+    /// Returns the time the slots of the current status read take, during
+    /// which the slaves respond with gaps of silence in between them
+    pub fn response_window(&self) -> Option<Duration> {
+        (self.status_slots > 0).then(|| self.slot_time * self.status_slots as u32)
+    }
+
+    /// Starts a new request, encoding the frame to be transmitted
+    /// # Arguments
+    /// * `frame` - The frame to issue on the bus
+    pub fn start(&mut self, frame: &Frame) -> Result<(), Error> {
+        if self.is_busy() {
+            return Err(Error::Busy);
+        }
+
+        // The payload of a read is sent by the slave, not by us
+        if let Frame::MemRead {
+            address, payload, ..
+        } = frame
+        {
+            if !payload.is_empty() {
+                return Err(Error::Encode(EncodeError::InvalidPayloadLength));
+            }
+
+            if *address == SlaveAddress::Broadcast && !self.forwarding {
+                return Err(Error::Unsupported);
+            }
+        }
+
+        // The slaves time their slots themselves, which does
+        // not work with the frame passing through all of them
+        if let Frame::StatusRead { .. } = frame {
+            if self.forwarding {
+                return Err(Error::Unsupported);
+            }
+        }
+
+        // Only slaves passing on the position can tell their positions apart
+        if let Frame::MemRead {
+            address: SlaveAddress::Position(_),
+            ..
+        }
+        | Frame::MemWrite {
+            address: SlaveAddress::Position(_),
+            ..
+        } = frame
+        {
+            if !self.forwarding {
+                return Err(Error::Unsupported);
+            }
+        }
+
+        let sequence_no = (self.sequence_no + 1) & 0b11;
+
+        self.tx
+            .resize(frame.encoded_len().map_err(Error::Encode)?, 0);
+        let len = frame
+            .encode(sequence_no, &mut self.tx)
+            .map_err(Error::Encode)?;
+        self.tx.truncate(len);
+
+        self.sequence_no = sequence_no;
+        self.rx.clear();
+        self.rx_size = match frame {
+            Frame::MemRead { size, .. } => *size as usize,
+            // The logical address of the slave precedes the data
+            Frame::StatusRead { size, .. } => *size as usize + 2,
+            _ => 0,
+        };
+        (self.status_slots, self.slot_time) = match frame {
+            Frame::StatusRead {
+                slots, slot_time, ..
+            } => (*slots as usize, Duration::from_micros(*slot_time as u64)),
+            _ => (0, Duration::ZERO),
+        };
+        self.reading = matches!(frame, Frame::MemRead { .. });
+        self.searching = matches!(frame, Frame::Search { .. });
+        self.pos = 0;
+        self.result = None;
+
+        self.acks = match frame {
+            Frame::MemRead { .. } | Frame::MemWrite { .. } => self.ack_slots as usize,
+            _ => 0,
+        };
+        self.working_counter = (self.acks > 0).then_some(0);
+
+        // Every frame from the master ends in a CRC. For reads,
+        // this is the header CRC and the slave sends the final one
+        self.crc = CRC8Autosar::new().update_move(&self.tx);
+
+        self.echo = 0;
+        if self.forwarding && self.reading {
+            self.echo = self.tx.len();
+
+            let placeholders = CRC8Autosar::new()
+                .update_move(&self.tx)
+                .update_move(&vec![0u8; self.rx_size]);
+            self.tx.resize(self.tx.len() + self.rx_size, 0);
+            self.tx.push(placeholders.finalize());
+
+            // Slaves may modify the header on its way, so the
+            // CRC covers the header as it returns to us
+            self.crc = CRC8Autosar::new();
+        }
+
+        // Matching slaves replace the empty response to a search
+        if self.forwarding && self.searching {
+            self.echo = self.tx.len();
+            self.tx.push(0);
+        }
+
+        // The slaves count in the ack slots as they pass, which we transmit
+        // empty. For writes, we skip the complete frame as it returns.
+        if self.forwarding && self.acks > 0 {
+            if !self.reading {
+                self.echo = self.tx.len();
+            }
+            self.tx.resize(self.tx.len() + self.acks, 0);
+        }
+
+        self.state = State::Transmit;
+        Ok(())
+    }
+
+    /// Aborts the current request, if any
+    pub fn abort(&mut self) {
+        if self.is_busy() {
+            self.finish(Err(Error::Aborted));
+        }
+    }
+
+    /// Stops waiting for the remaining ack slots or status read slots of
+    /// the current request, which are left empty by slaves that are
+    /// missing or out of sync
+    /// # Returns
+    /// Whether the request has been finished by this
+    pub fn expire_slots(&mut self) -> bool {
+        match self.state {
+            State::RxAckSlots => {
+                self.acks = 0;
+                self.acknowledge();
+                true
+            }
+            // A response cut short by the end of the slots is not complete
+            State::RxStatus if self.pos == 0 => {
+                let data = core::mem::take(&mut self.rx);
+                self.finish(Ok(Response::Data(data)));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Takes the result of the last finished request
+    pub fn take_result(&mut self) -> Option<Result<Response, Error>> {
+        self.result.take()
+    }
+
+    /// Process some event in the state machine of the transceiver.
+    /// # Arguments
+    /// * `rx` - An incoming byte from the physical layer
+    /// # Returns
+    /// A byte to be sent via the physical layer, if any
+    pub fn handle(&mut self, rx: Option<u8>) -> Option<u8> {
+        match self.state {
+            State::Idle => None,
+            State::Transmit => {
+                let tx = self.tx[self.pos];
+                self.pos += 1;
+
+                if self.pos >= self.tx.len() {
+                    self.pos = 0;
+                    if self.echo > 0 {
+                        self.state = State::RxEcho;
+                    } else {
+                        self.receive();
+                    }
+                }
+
+                Some(tx)
+            }
+            State::RxEcho => {
+                if let Some(rx) = rx {
+                    self.crc.update_single(rx);
+                    self.echo -= 1;
+
+                    if self.echo == 0 {
+                        self.receive();
+                    }
+                }
+
+                None
+            }
+            State::RxPayload => {
+                if let Some(rx) = rx {
+                    self.crc.update_single(rx);
+                    self.rx.push(rx);
+
+                    if self.rx.len() >= self.rx_size {
+                        self.state = State::RxCRC;
+                    }
+                }
+
+                None
+            }
+            State::RxCRC => {
+                if let Some(rx) = rx {
+                    let expected = self.crc.finalize();
+                    if expected == rx {
+                        self.acknowledge();
+                    } else {
+                        test_log!("CRC mismatch: expected 0x{expected:x}, got 0x{rx:x}");
+                        self.finish(Err(Error::CRCMismatch {
+                            expected,
+                            received: rx,
+                        }));
+                    }
+                }
+
+                None
+            }
+            State::RxSearchResponse => {
+                if let Some(rx) = rx {
+                    self.finish(Ok(Response::Data(vec![rx])));
+                }
+
+                None
+            }
+            State::RxStatus => {
+                let rx = rx?;
+
+                // Every response ends in a CRC over the response alone
+                if self.pos < self.rx_size {
+                    self.crc.update_single(rx);
+                    self.rx.push(rx);
+                    self.pos += 1;
+                    return None;
+                }
+
+                let expected = self.crc.finalize();
+                if expected != rx {
+                    test_log!("CRC mismatch: expected 0x{expected:x}, got 0x{rx:x}");
+                    self.finish(Err(Error::CRCMismatch {
+                        expected,
+                        received: rx,
+                    }));
+                } else if self.rx.len() > self.rx_size * self.status_slots {
+                    self.finish(Err(Error::InvalidResponse));
+                } else {
+                    self.crc = CRC8Autosar::new();
+                    self.pos = 0;
+                }
+
+                None
+            }
+            State::RxAckSlots => {
+                if let Some(rx) = rx {
+                    self.working_counter = self.working_counter.map(|c| c + rx as u16);
+                    self.acks -= 1;
+                    self.acknowledge();
+                }
+
+                None
+            }
+        }
+    }
+
+    /// Starts receiving the response of the slaves after the frame
+    fn receive(&mut self) {
+        if self.status_slots > 0 {
+            // The slots pass until the transport expires them
+            self.crc = CRC8Autosar::new();
+            self.state = State::RxStatus;
+        } else if self.searching {
+            self.state = State::RxSearchResponse;
+        } else if !self.reading {
+            self.acknowledge();
+        } else if self.rx_size > 0 {
+            self.state = State::RxPayload;
+        } else {
+            self.state = State::RxCRC;
+        }
+    }
+
+    /// Receives the remaining ack slots or finishes the request
+    fn acknowledge(&mut self) {
+        if self.acks > 0 {
+            self.state = State::RxAckSlots;
+        } else if self.reading {
+            let data = core::mem::take(&mut self.rx);
+            self.finish(Ok(Response::Data(data)));
+        } else {
+            self.finish(Ok(Response::None));
+        }
+    }
+
+    fn finish(&mut self, result: Result<Response, Error>) {
+        self.state = State::Idle;
+        self.result = Some(result);
+    }
+}
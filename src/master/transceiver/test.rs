@@ -0,0 +1,299 @@
+use std::time::Duration;
+
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    frame::{Frame, SlaveAddress},
+    master::transceiver::{Error, Response, Transceiver},
+    slave::transceiver::{CallbackAction, Transceiver as SlaveTransceiver},
+    CMD_SYNC, PROTOCOL_VERSION_1, START_BYTE, SYNC_SEQUENCE,
+};
+
+/// Collect all bytes the master transmits for its current request
+fn collect_tx(t: &mut Transceiver) -> Vec<u8> {
+    let mut tx = Vec::new();
+    while t.is_busy() && !t.is_receiving() {
+        tx.push(
+            t.handle(None)
+                .expect("Transmitting master returned no byte"),
+        );
+    }
+    tx
+}
+
+/// Run a request of the master against a slave until it is finished
+fn run(master: &mut Transceiver, slave: &mut SlaveTransceiver, frame: Frame) -> Response {
+    master.start(&frame).unwrap();
+
+    while master.is_busy() {
+        let tx = master.handle(None);
+        let mut response = slave.handle(tx);
+        while let Some(rx) = response {
+            master.handle(Some(rx));
+            response = slave.handle(None);
+        }
+    }
+
+    master.take_result().unwrap().unwrap()
+}
+
+fn counting_callback(action: CallbackAction) -> Result<(), ()> {
+    if let CallbackAction::ReadMemory { offset, data } = action {
+        for (i, d) in data.iter_mut().enumerate() {
+            *d = offset as u8 + i as u8;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn encode_sync() {
+    let mut t = Transceiver::new();
+    t.start(&Frame::Sync {
+        version: PROTOCOL_VERSION_1,
+    })
+    .unwrap();
+
+    let mut expected = vec![START_BYTE, CMD_SYNC | 1 << 6];
+    expected.extend_from_slice(&SYNC_SEQUENCE);
+    expected.push(PROTOCOL_VERSION_1);
+    expected.push(CRC8Autosar::new().update_move(&expected).finalize());
+
+    assert_eq!(collect_tx(&mut t), expected);
+    assert!(!t.is_busy());
+    assert_eq!(t.take_result(), Some(Ok(Response::None)));
+}
+
+#[test]
+#[allow(clippy::identity_op)]
+fn encode_write_long_offset() {
+    let mut t = Transceiver::new();
+    t.start(&Frame::MemWrite {
+        address: SlaveAddress::Logical([5, 6]),
+        offset: 0x1234,
+        payload: &[0xAA],
+    })
+    .unwrap();
+
+    let cmd_byte = 1 << 6 // Sequence number
+        | 1 << 5 // Perform a memory command
+        | 1 << 3 // Long offset
+        | 2 << 1 // Addressed by logical address
+        | 1 << 0; // Operation: Write
+    let mut expected = vec![START_BYTE, cmd_byte, 5, 6, 0x12, 0x34, 1, 0xAA];
+    expected.push(CRC8Autosar::new().update_move(&expected).finalize());
+
+    assert_eq!(collect_tx(&mut t), expected);
+}
+
+#[test]
+fn sequence_rolls_over() {
+    let mut t = Transceiver::new();
+    for expected in [1, 2, 3, 0, 1] {
+        t.start(&Frame::Nop).unwrap();
+        let tx = collect_tx(&mut t);
+        assert_eq!(tx[1] >> 6, expected);
+    }
+}
+
+#[test]
+fn busy() {
+    let mut t = Transceiver::new();
+    t.start(&Frame::Nop).unwrap();
+    assert_eq!(t.start(&Frame::Nop), Err(Error::Busy));
+
+    t.abort();
+    assert_eq!(t.take_result(), Some(Err(Error::Aborted)));
+    assert!(t.start(&Frame::Nop).is_ok());
+}
+
+#[test]
+fn read_from_slave() {
+    let mut scratchpad = [0u8; 0xf];
+    let addr = [1, 2, 3, 4, 5, 6];
+    let mut slave = SlaveTransceiver::new(&mut scratchpad, addr, counting_callback);
+    let mut master = Transceiver::new();
+
+    run(
+        &mut master,
+        &mut slave,
+        Frame::Sync {
+            version: PROTOCOL_VERSION_1,
+        },
+    );
+    assert!(slave.in_sync());
+
+    for size in 0..4 {
+        let res = run(
+            &mut master,
+            &mut slave,
+            Frame::MemRead {
+                address: SlaveAddress::Physical(addr),
+                offset: 0x10,
+                size,
+                payload: &[],
+            },
+        );
+        let expected: Vec<u8> = (0..size as u8).map(|i| 0x10 + i).collect();
+        assert_eq!(res, Response::Data(expected));
+        assert!(slave.in_sync());
+    }
+}
+
+#[test]
+fn read_crc_mismatch() {
+    let mut t = Transceiver::new();
+    t.start(&Frame::MemRead {
+        address: SlaveAddress::Logical([1, 2]),
+        offset: 0,
+        size: 1,
+        payload: &[],
+    })
+    .unwrap();
+    let tx = collect_tx(&mut t);
+    assert!(t.is_receiving());
+
+    let crc = CRC8Autosar::new().update_move(&tx).update_single_move(0x42);
+    t.handle(Some(0x42));
+    t.handle(Some(!crc.finalize()));
+
+    assert_eq!(
+        t.take_result(),
+        Some(Err(Error::CRCMismatch {
+            expected: crc.finalize(),
+            received: !crc.finalize()
+        }))
+    );
+}
+
+#[test]
+fn ack_slots() {
+    let mut t = Transceiver::new();
+    t.set_ack_slots(2);
+
+    t.start(&Frame::MemWrite {
+        address: SlaveAddress::Broadcast,
+        offset: 0,
+        payload: &[1],
+    })
+    .unwrap();
+    collect_tx(&mut t);
+    assert!(t.is_receiving());
+
+    t.handle(Some(1));
+    t.handle(Some(1));
+    assert_eq!(t.take_result(), Some(Ok(Response::None)));
+    assert_eq!(t.working_counter(), Some(2));
+
+    // Management commands are not acknowledged
+    t.start(&Frame::Nop).unwrap();
+    collect_tx(&mut t);
+    assert_eq!(t.take_result(), Some(Ok(Response::None)));
+    assert_eq!(t.working_counter(), None);
+}
+
+#[test]
+fn ack_slots_expire() {
+    let mut t = Transceiver::new();
+    t.set_ack_slots(3);
+    assert!(!t.expire_slots());
+
+    t.start(&Frame::MemRead {
+        address: SlaveAddress::Logical([1, 2]),
+        offset: 0,
+        size: 1,
+        payload: &[],
+    })
+    .unwrap();
+    let tx = collect_tx(&mut t);
+
+    let crc = CRC8Autosar::new().update_move(&tx).update_single_move(0x42);
+    t.handle(Some(0x42));
+    t.handle(Some(crc.finalize()));
+    t.handle(Some(1));
+
+    // The remaining slots have been left empty
+    assert!(t.expire_slots());
+    assert_eq!(t.take_result(), Some(Ok(Response::Data(vec![0x42]))));
+    assert_eq!(t.working_counter(), Some(1));
+}
+
+#[test]
+fn broadcast_read() {
+    let mut t = Transceiver::new();
+    let frame = Frame::MemRead {
+        address: SlaveAddress::Broadcast,
+        offset: 0,
+        size: 1,
+        payload: &[],
+    };
+    assert_eq!(t.start(&frame), Err(Error::Unsupported));
+
+    t.set_forwarding(true);
+    assert!(t.start(&frame).is_ok());
+}
+
+#[test]
+fn status_read() {
+    let frame = Frame::StatusRead {
+        first: [1, 0],
+        slots: 3,
+        slot_time: 1000,
+        offset: 0,
+        size: 1,
+    };
+
+    let mut t = Transceiver::new();
+    t.start(&frame).unwrap();
+    assert_eq!(t.response_window(), Some(Duration::from_millis(3)));
+    collect_tx(&mut t);
+
+    // The responses of the slaves in the first and third slot
+    for response in [[1, 0, 0xA], [3, 0, 0xC]] {
+        for b in response {
+            t.handle(Some(b));
+        }
+        t.handle(Some(CRC8Autosar::new().update_move(&response).finalize()));
+    }
+    assert!(t.is_receiving());
+
+    assert!(t.expire_slots());
+    assert_eq!(
+        t.take_result(),
+        Some(Ok(Response::Data(vec![1, 0, 0xA, 3, 0, 0xC])))
+    );
+
+    // Corrupted responses are rejected
+    t.start(&frame).unwrap();
+    collect_tx(&mut t);
+    for b in [2, 0, 0xB, 0] {
+        t.handle(Some(b));
+    }
+    assert!(matches!(
+        t.take_result(),
+        Some(Err(Error::CRCMismatch { .. }))
+    ));
+
+    // Responses cut short are not complete
+    t.start(&frame).unwrap();
+    collect_tx(&mut t);
+    t.handle(Some(2));
+    assert!(!t.expire_slots());
+    t.abort();
+
+    t.set_forwarding(true);
+    assert_eq!(t.start(&frame), Err(Error::Unsupported));
+}
+
+#[test]
+fn position_addressing() {
+    let mut t = Transceiver::new();
+    let frame = Frame::MemWrite {
+        address: SlaveAddress::Position(0),
+        offset: 0,
+        payload: &[1],
+    };
+    assert_eq!(t.start(&frame), Err(Error::Unsupported));
+
+    t.set_forwarding(true);
+    assert!(t.start(&frame).is_ok());
+}
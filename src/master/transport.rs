@@ -0,0 +1,175 @@
+//! Transports move the bytes of the [master transceiver](Transceiver)
+//! over a physical medium to the slaves and back.
+
+#[cfg(feature = "master-transport-serial")]
+pub mod serial;
+#[cfg(feature = "master-transport-udp")]
+pub mod udp;
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    frame::Frame,
+    master::transceiver::{self, Response, Transceiver},
+};
+
+/// The timeouts a transport applies while waiting for a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// The maximum time to wait for a single byte
+    pub byte: Duration,
+
+    /// The maximum time to wait for the complete response
+    pub frame: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            byte: Duration::from_millis(10),
+            frame: Duration::from_millis(100),
+        }
+    }
+}
+
+/// The errors that can occur while transferring a frame
+#[derive(Debug)]
+pub enum TransportError {
+    /// The underlying medium failed
+    Io(std::io::Error),
+
+    /// The slave did not respond in time
+    Timeout,
+
+    /// The response violates the protocol
+    Protocol(transceiver::Error),
+}
+
+impl std::fmt::Display for TransportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::Timeout => write!(f, "Timed out waiting for a response"),
+            Self::Protocol(e) => write!(f, "Protocol error: {e:?}"),
+        }
+    }
+}
+
+impl std::error::Error for TransportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for TransportError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<transceiver::Error> for TransportError {
+    fn from(value: transceiver::Error) -> Self {
+        Self::Protocol(value)
+    }
+}
+
+/// A medium that can carry sondbus frames
+pub trait Transport {
+    /// Transmits `data` to the slaves
+    /// # Arguments
+    /// * `data` - The bytes to transmit
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError>;
+
+    /// Receives a single byte from the slaves
+    /// # Arguments
+    /// * `timeout` - The maximum time to wait for the byte
+    /// # Returns
+    /// The received byte or `None` if the timeout elapsed
+    fn receive(&mut self, timeout: Duration) -> Result<Option<u8>, TransportError>;
+
+    /// Returns the timeouts to apply while waiting for a response
+    fn timeouts(&self) -> Timeouts;
+
+    /// Returns whether the slaves process the frames on the fly and
+    /// return them to the master, as in a chain of slaves
+    fn forwarding(&self) -> bool {
+        false
+    }
+
+    /// Transfers a frame using `transceiver` and waits for the response
+    /// # Arguments
+    /// * `transceiver` - The transceiver to encode the frame and decode the response
+    /// * `frame` - The frame to transfer
+    fn transfer(
+        &mut self,
+        transceiver: &mut Transceiver,
+        frame: &Frame,
+    ) -> Result<Response, TransportError> {
+        transfer(self, transceiver, frame)
+    }
+}
+
+/// Transfers a frame over `transport` using `transceiver` and waits for the response.
+///
+/// This is the default implementation of [Transport::transfer],
+/// available to transports that wrap the transfer of another transport.
+/// # Arguments
+/// * `transport` - The transport to transfer the frame over
+/// * `transceiver` - The transceiver to encode the frame and decode the response
+/// * `frame` - The frame to transfer
+pub fn transfer<T: Transport + ?Sized>(
+    transport: &mut T,
+    transceiver: &mut Transceiver,
+    frame: &Frame,
+) -> Result<Response, TransportError> {
+    transceiver.set_forwarding(transport.forwarding());
+    transceiver.start(frame)?;
+
+    let mut tx = Vec::new();
+    while transceiver.is_busy() && !transceiver.is_receiving() {
+        tx.extend(transceiver.handle(None));
+    }
+
+    if let Err(e) = transport.send(&tx) {
+        transceiver.abort();
+        return Err(e);
+    }
+
+    // The slots of a status read are received until they have passed,
+    // as the slots of missing slaves leave gaps of arbitrary length.
+    // The byte timeout covers the latency of the medium.
+    let timeouts = transport.timeouts();
+    let window = transceiver.response_window();
+    let deadline = Instant::now() + window.map_or(timeouts.frame, |w| w + timeouts.byte);
+
+    while transceiver.is_receiving() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let timeout = match window {
+            Some(_) => remaining,
+            None => remaining.min(timeouts.byte),
+        };
+
+        let rx = match transport.receive(timeout) {
+            Ok(Some(rx)) if !remaining.is_zero() => rx,
+            Ok(_) if transceiver.expire_slots() => break,
+            Ok(_) => {
+                transceiver.abort();
+                return Err(TransportError::Timeout);
+            }
+            Err(e) => {
+                transceiver.abort();
+                return Err(e);
+            }
+        };
+
+        transceiver.handle(Some(rx));
+    }
+
+    match transceiver.take_result() {
+        Some(res) => Ok(res?),
+        None => Err(TransportError::Protocol(transceiver::Error::Aborted)),
+    }
+}
@@ -0,0 +1,82 @@
+//! A transport for serial ports, such as UARTs or RS-485 adapters
+
+#[cfg(test)]
+mod test;
+
+use std::{io::ErrorKind, path::Path, time::Duration};
+
+use serial2::SerialPort;
+
+pub use serial2::Parity;
+
+use crate::master::transport::{Timeouts, Transport, TransportError};
+
+/// A transport that carries the frames over a serial port
+pub struct SerialTransport {
+    port: SerialPort,
+    timeouts: Timeouts,
+}
+
+impl SerialTransport {
+    /// Opens and configures a serial port
+    /// # Arguments
+    /// * `path` - The path to the serial port
+    /// * `baud_rate` - The baud rate to configure
+    /// * `parity` - The parity to configure
+    pub fn open(path: impl AsRef<Path>, baud_rate: u32, parity: Parity) -> std::io::Result<Self> {
+        let port = SerialPort::open(path, |mut settings: serial2::Settings| {
+            settings.set_raw();
+            settings.set_baud_rate(baud_rate)?;
+            settings.set_parity(parity);
+            Ok(settings)
+        })?;
+
+        Ok(Self::from_port(port))
+    }
+
+    /// Creates a new transport from an already configured serial port
+    /// # Arguments
+    /// * `port` - The serial port to use
+    pub fn from_port(port: SerialPort) -> Self {
+        Self {
+            port,
+            timeouts: Timeouts::default(),
+        }
+    }
+
+    /// Sets the timeouts to apply while waiting for a response
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = timeouts;
+    }
+
+    /// Returns the underlying serial port
+    pub fn port(&self) -> &SerialPort {
+        &self.port
+    }
+}
+
+impl Transport for SerialTransport {
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        // Drop anything left over from previous frames, so that
+        // it is not mistaken for the response to this one
+        self.port.discard_input_buffer()?;
+        self.port.write_all(data)?;
+        Ok(())
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<Option<u8>, TransportError> {
+        self.port.set_read_timeout(timeout)?;
+
+        let mut buf = [0u8; 1];
+        match self.port.read(&mut buf) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(buf[0])),
+            Err(e) if e.kind() == ErrorKind::TimedOut => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use serial2::SerialPort;
+use sondbus_derive::SondbusMemory;
+
+use crate::{
+    frame::SlaveAddress,
+    master::{
+        transceiver::Error,
+        transport::{serial::SerialTransport, TransportError},
+        Master,
+    },
+    slave::transceiver::{Callback, CallbackAction, SlaveMemory, Transceiver},
+    system::{FeatureSupport, MMU_COUNT},
+    PROTOCOL_VERSION_1,
+};
+
+const SLAVE_ADDR: [u8; 6] = [1, 2, 3, 4, 5, 6];
+const ADDRESS: SlaveAddress = SlaveAddress::Physical(SLAVE_ADDR);
+
+fn counting_callback(action: CallbackAction) -> Result<(), ()> {
+    if let CallbackAction::ReadMemory { offset, data } = action {
+        for (i, d) in data.iter_mut().enumerate() {
+            *d = offset as u8 + i as u8;
+        }
+    }
+    Ok(())
+}
+
+/// Runs a slave on the `port` until `stop` is set
+fn spawn_slave(port: SerialPort, stop: Arc<AtomicBool>) -> JoinHandle<()> {
+    spawn_slave_with(port, stop, counting_callback as Callback)
+}
+
+/// Runs a slave operating on `memory` on the `port` until `stop` is set
+fn spawn_slave_with<M: SlaveMemory + Send + 'static>(
+    port: SerialPort,
+    stop: Arc<AtomicBool>,
+    memory: M,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut port = port;
+        port.set_read_timeout(Duration::from_millis(5)).unwrap();
+
+        let mut scratchpad = [0u8; 32];
+        let mut t = Transceiver::with_memory(&mut scratchpad, SLAVE_ADDR, memory);
+
+        let mut buf = [0u8; 64];
+        while !stop.load(Ordering::Relaxed) {
+            let len = port.read(&mut buf).unwrap_or(0);
+
+            let mut response = Vec::new();
+            for b in &buf[..len] {
+                response.extend(t.handle(Some(*b)));
+                while let Some(tx) = t.handle(None) {
+                    response.push(tx);
+                }
+            }
+
+            if !response.is_empty() {
+                port.write_all(&response).unwrap();
+            }
+        }
+    })
+}
+
+#[test]
+fn read_write() {
+    let (master_port, slave_port) = SerialPort::pair().unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let slave = spawn_slave(slave_port, stop.clone());
+
+    let mut master = Master::new(SerialTransport::from_port(master_port));
+    master.sync().unwrap();
+
+    master
+        .write(SlaveAddress::Physical(SLAVE_ADDR), 0, &[1, 2, 3])
+        .unwrap();
+
+    let data = master
+        .read(SlaveAddress::Physical(SLAVE_ADDR), 0x20, 4)
+        .unwrap();
+    assert_eq!(data, vec![0x20, 0x21, 0x22, 0x23]);
+
+    stop.store(true, Ordering::Relaxed);
+    slave.join().unwrap();
+}
+
+#[test]
+fn read_capabilities() {
+    let (master_port, slave_port) = SerialPort::pair().unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let slave = spawn_slave(slave_port, stop.clone());
+
+    let mut master = Master::new(SerialTransport::from_port(master_port));
+    master.sync().unwrap();
+
+    let caps = master
+        .read_capabilities(SlaveAddress::Physical(SLAVE_ADDR))
+        .unwrap();
+    assert!(caps.supports_version(PROTOCOL_VERSION_1));
+    assert_eq!(caps.scratchpad_size, 32);
+    assert_eq!(caps.long_size, FeatureSupport::Partial);
+    assert_eq!(caps.mmu_count, MMU_COUNT as u8);
+
+    stop.store(true, Ordering::Relaxed);
+    slave.join().unwrap();
+}
+
+#[derive(SondbusMemory, Default)]
+struct Layout {
+    #[sondbus(read_only)]
+    status: u16,
+    setpoint: [i16; 2],
+}
+
+#[test]
+fn read_write_field() {
+    let (master_port, slave_port) = SerialPort::pair().unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let memory = Layout {
+        status: 0x1234,
+        ..Default::default()
+    };
+    let slave = spawn_slave_with(slave_port, stop.clone(), memory);
+
+    let mut master = Master::new(SerialTransport::from_port(master_port));
+    master.sync().unwrap();
+
+    assert_eq!(master.read_field(ADDRESS, Layout::STATUS).unwrap(), 0x1234);
+
+    master
+        .write_field(ADDRESS, Layout::SETPOINT, [-1, 300])
+        .unwrap();
+    assert_eq!(
+        master.read_field(ADDRESS, Layout::SETPOINT).unwrap(),
+        [-1, 300]
+    );
+
+    // The slave rejects writes to read-only fields and drops out of sync
+    master.write_field(ADDRESS, Layout::STATUS, 0).unwrap();
+    let res = master.read_field(ADDRESS, Layout::STATUS);
+    assert!(matches!(res, Err(TransportError::Timeout)), "{res:?}");
+
+    stop.store(true, Ordering::Relaxed);
+    slave.join().unwrap();
+}
+
+#[test]
+fn timeout() {
+    let (master_port, slave_port) = SerialPort::pair().unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let slave = spawn_slave(slave_port, stop.clone());
+
+    let mut master = Master::new(SerialTransport::from_port(master_port));
+    master.sync().unwrap();
+
+    // Nobody is listening on this address
+    let res = master.read(SlaveAddress::Physical([6, 5, 4, 3, 2, 1]), 0, 1);
+    assert!(matches!(res, Err(TransportError::Timeout)), "{res:?}");
+
+    stop.store(true, Ordering::Relaxed);
+    slave.join().unwrap();
+}
+
+#[test]
+fn protocol_error() {
+    let (master_port, slave_port) = SerialPort::pair().unwrap();
+
+    // A misbehaving slave that answers everything with garbage
+    let responder = std::thread::spawn(move || {
+        let mut buf = [0u8; 64];
+        slave_port.read(&mut buf).unwrap();
+        slave_port.write_all(&[0x00, 0x00]).unwrap();
+    });
+
+    let mut master = Master::new(SerialTransport::from_port(master_port));
+    let res = master.read(SlaveAddress::Logical([1, 2]), 0, 1);
+    assert!(
+        matches!(
+            res,
+            Err(TransportError::Protocol(Error::CRCMismatch { .. }))
+        ),
+        "{res:?}"
+    );
+
+    responder.join().unwrap();
+}
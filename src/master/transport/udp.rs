@@ -0,0 +1,225 @@
+//! A transport for UDP sockets, carrying one frame per datagram.
+//!
+//! Every frame is sent to all slaves registered with the transport,
+//! emulating a shared medium. The slaves are hosted using a
+//! [UdpSlave](crate::slave::udp::UdpSlave). As the responses of multiple
+//! slaves arrive in separate datagrams in no particular order, logical
+//! memory reads can only be served by a single slave. If the working
+//! counter is enabled, every slave in sync responds to memory commands
+//! with its ack slots. These responses are collected until all slaves
+//! responded or the frame timeout elapsed, summing up their ack slots.
+
+#[cfg(test)]
+mod test;
+
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    frame::Frame,
+    master::{
+        transceiver::{Response, Transceiver},
+        transport::{self, Timeouts, Transport, TransportError},
+    },
+};
+
+/// The largest datagram that can be carried over UDP
+const MAX_DATAGRAM: usize = u16::MAX as usize;
+
+/// The responses to collect from all slaves for the current frame
+#[derive(Debug, Clone, Copy)]
+struct Collect {
+    /// The amount of ack slots ending every response
+    slots: usize,
+    /// Whether a slave responds with data in front of the ack slots
+    reading: bool,
+}
+
+/// A transport that carries the frames over a UDP socket
+pub struct UdpTransport {
+    socket: UdpSocket,
+    slaves: Vec<SocketAddr>,
+    rx: VecDeque<u8>,
+    buf: Vec<u8>,
+    timeouts: Timeouts,
+    collect: Option<Collect>,
+}
+
+impl UdpTransport {
+    /// Binds a new socket for the transport
+    /// # Arguments
+    /// * `addr` - The local address to receive the responses on
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::from_socket(UdpSocket::bind(addr)?))
+    }
+
+    /// Creates a new transport from an already bound socket
+    /// # Arguments
+    /// * `socket` - The socket to use
+    pub fn from_socket(socket: UdpSocket) -> Self {
+        Self {
+            socket,
+            slaves: Vec::new(),
+            rx: VecDeque::new(),
+            buf: vec![0u8; MAX_DATAGRAM],
+            timeouts: Timeouts::default(),
+            collect: None,
+        }
+    }
+
+    /// Registers a slave to send the frames to
+    /// # Arguments
+    /// * `addr` - The address the slave receives the frames on
+    pub fn add_slave(&mut self, addr: impl ToSocketAddrs) -> io::Result<&mut Self> {
+        self.slaves.extend(addr.to_socket_addrs()?);
+        Ok(self)
+    }
+
+    /// Sets the timeouts to apply while waiting for a response
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = timeouts;
+    }
+
+    /// Returns the underlying socket
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Drops all datagrams that are still pending on the socket
+    fn discard_input(&mut self) -> io::Result<()> {
+        self.socket.set_nonblocking(true)?;
+        let res = loop {
+            match self.socket.recv_from(&mut self.buf) {
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        self.socket.set_nonblocking(false)?;
+        res
+    }
+
+    /// Receives the responses of all slaves until every slave
+    /// responded or the frame timeout elapsed, merging them into
+    /// the data of the first response and the sum of all ack slots
+    fn collect(&mut self, collect: Collect) -> Result<(), TransportError> {
+        let deadline = Instant::now() + self.timeouts.frame;
+        let mut responded = Vec::new();
+        let mut data = None;
+        let mut acks = vec![0u8; collect.slots];
+
+        while responded.len() < self.slaves.len() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            self.socket.set_read_timeout(Some(remaining))?;
+
+            let (len, src) = match self.socket.recv_from(&mut self.buf) {
+                Ok(res) => res,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break,
+                Err(e) => return Err(e.into()),
+            };
+            if !self.slaves.contains(&src) || responded.contains(&src) {
+                continue;
+            }
+            responded.push(src);
+
+            let Some(split) = len.checked_sub(collect.slots) else {
+                continue;
+            };
+            let (payload, slots) = self.buf[..len].split_at(split);
+            if !payload.is_empty() && data.is_none() {
+                data = Some(payload.to_vec());
+            }
+            for (ack, slot) in acks.iter_mut().zip(slots) {
+                *ack = ack.saturating_add(*slot);
+            }
+        }
+
+        // Without the data, the ack slots of a read would be taken for it
+        match data {
+            Some(data) => self.rx.extend(data),
+            None if collect.reading => return Ok(()),
+            None => {}
+        }
+        self.rx.extend(acks);
+        Ok(())
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        // Drop anything left over from previous frames, so that
+        // it is not mistaken for the response to this one
+        self.discard_input()?;
+        self.rx.clear();
+
+        for slave in &self.slaves {
+            self.socket.send_to(data, slave)?;
+        }
+
+        match self.collect {
+            Some(collect) => self.collect(collect),
+            None => Ok(()),
+        }
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<Option<u8>, TransportError> {
+        // The responses have all been collected while sending
+        if self.collect.is_some() {
+            return Ok(self.rx.pop_front());
+        }
+
+        while self.rx.is_empty() {
+            // A zero timeout would block forever
+            if timeout.is_zero() {
+                return Ok(None);
+            }
+            self.socket.set_read_timeout(Some(timeout))?;
+
+            let (len, src) = match self.socket.recv_from(&mut self.buf) {
+                Ok(res) => res,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    return Ok(None)
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            if self.slaves.contains(&src) {
+                self.rx.extend(&self.buf[..len]);
+            }
+        }
+
+        Ok(self.rx.pop_front())
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+
+    fn transfer(
+        &mut self,
+        transceiver: &mut Transceiver,
+        frame: &Frame,
+    ) -> Result<Response, TransportError> {
+        // Every slave in sync responds to memory commands with its ack slots
+        self.collect = match frame {
+            Frame::MemRead { .. } | Frame::MemWrite { .. } if transceiver.ack_slots() > 0 => {
+                Some(Collect {
+                    slots: transceiver.ack_slots() as usize,
+                    reading: matches!(frame, Frame::MemRead { .. }),
+                })
+            }
+            _ => None,
+        };
+
+        let res = transport::transfer(self, transceiver, frame);
+        self.collect = None;
+        res
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use sondbus_derive::SondbusMemory;
+
+use crate::{
+    frame::SlaveAddress,
+    master::{
+        transport::{udp::UdpTransport, TransportError},
+        Master,
+    },
+    slave::{transceiver::Transceiver, udp::UdpSlave},
+};
+
+#[derive(SondbusMemory, Default)]
+struct Regs {
+    data: [u8; 4],
+}
+
+fn mac(i: u8) -> [u8; 6] {
+    [0, 0, 0, 0, 0, i]
+}
+
+/// Runs a slave with the physical address `mac` until `stop` is set
+fn spawn_slave(mac: [u8; 6], stop: Arc<AtomicBool>) -> (SocketAddr, JoinHandle<Regs>) {
+    let mut slave = UdpSlave::bind("127.0.0.1:0").unwrap();
+    let addr = slave.local_addr().unwrap();
+
+    let handle = std::thread::spawn(move || {
+        let mut scratchpad = [0u8; 32];
+        let mut t = Transceiver::with_memory(&mut scratchpad, mac, Regs::default());
+
+        while !stop.load(Ordering::Relaxed) {
+            slave.poll(&mut t, Some(Duration::from_millis(5))).unwrap();
+        }
+
+        core::mem::take(t.memory())
+    });
+
+    (addr, handle)
+}
+
+/// Runs `count` slaves and a master connected to all of them
+fn setup(count: u8) -> (Master<UdpTransport>, Arc<AtomicBool>, Vec<JoinHandle<Regs>>) {
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut transport = UdpTransport::bind("127.0.0.1:0").unwrap();
+
+    let mut slaves = Vec::new();
+    for i in 0..count {
+        let (addr, handle) = spawn_slave(mac(i), stop.clone());
+        transport.add_slave(addr).unwrap();
+        slaves.push(handle);
+    }
+
+    (Master::new(transport), stop, slaves)
+}
+
+/// Stops the slaves and returns their memory
+fn stop(stop: Arc<AtomicBool>, slaves: Vec<JoinHandle<Regs>>) -> Vec<[u8; 4]> {
+    stop.store(true, Ordering::Relaxed);
+    slaves.into_iter().map(|s| s.join().unwrap().data).collect()
+}
+
+#[test]
+fn many_slaves() {
+    let (mut master, s, slaves) = setup(16);
+
+    // There is no need for a sync on a framed link
+    master
+        .write(SlaveAddress::Broadcast, 0, &[1, 1, 1, 1])
+        .unwrap();
+
+    for i in 0..16 {
+        master
+            .write(SlaveAddress::Physical(mac(i)), 3, &[i])
+            .unwrap();
+    }
+
+    master.assign_logical_address(mac(5), [0, 5]).unwrap();
+    let data = master.read(SlaveAddress::Logical([0, 5]), 0, 4).unwrap();
+    assert_eq!(data, vec![1, 1, 1, 5]);
+
+    for (i, data) in stop(s, slaves).into_iter().enumerate() {
+        assert_eq!(data, [1, 1, 1, i as u8]);
+    }
+}
+
+#[test]
+fn recovers_without_sync() {
+    let (mut master, s, slaves) = setup(1);
+
+    // The slave rejects the read and drops out of sync
+    let res = master.read(SlaveAddress::Physical(mac(0)), 2, 4);
+    assert!(matches!(res, Err(TransportError::Timeout)), "{res:?}");
+
+    // The next frame is handled again
+    master
+        .write(SlaveAddress::Physical(mac(0)), 0, &[1, 2])
+        .unwrap();
+    let data = master.read(SlaveAddress::Physical(mac(0)), 0, 4).unwrap();
+    assert_eq!(data, vec![1, 2, 0, 0]);
+
+    stop(s, slaves);
+}
+
+#[test]
+fn timeout() {
+    let (mut master, s, slaves) = setup(1);
+
+    // Nobody is listening on this address
+    let res = master.read(SlaveAddress::Physical(mac(1)), 0, 1);
+    assert!(matches!(res, Err(TransportError::Timeout)), "{res:?}");
+
+    stop(s, slaves);
+}
+
+#[test]
+fn working_counter() {
+    let (mut master, s, slaves) = setup(1);
+
+    master
+        .assign_ack_slot(SlaveAddress::Physical(mac(0)), 1)
+        .unwrap();
+    master.configure_working_counter(2).unwrap();
+
+    // The slave fills the slot in front of its own
+    master
+        .write_acknowledged(SlaveAddress::Physical(mac(0)), 0, &[1], 1)
+        .unwrap();
+    let data = master.read(SlaveAddress::Physical(mac(0)), 0, 1).unwrap();
+    assert_eq!(data, vec![1]);
+    assert_eq!(master.working_counter(), Some(1));
+
+    stop(s, slaves);
+}
+
+#[test]
+fn working_counter_many_slaves() {
+    let (mut master, s, slaves) = setup(4);
+
+    // A slave that never responds
+    let missing = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    master
+        .transport()
+        .add_slave(missing.local_addr().unwrap())
+        .unwrap();
+
+    for i in 0..4 {
+        master
+            .assign_ack_slot(SlaveAddress::Physical(mac(i)), i)
+            .unwrap();
+    }
+    master.configure_working_counter(5).unwrap();
+
+    // The ack slots of all responses are summed up
+    master
+        .write_acknowledged(SlaveAddress::Broadcast, 0, &[1, 2], 4)
+        .unwrap();
+
+    // The other slaves respond with their ack slots only
+    let data = master.read(SlaveAddress::Physical(mac(2)), 0, 4).unwrap();
+    assert_eq!(data, vec![1, 2, 0, 0]);
+    assert_eq!(master.working_counter(), Some(1));
+
+    for data in stop(s, slaves) {
+        assert_eq!(data, [1, 2, 0, 0]);
+    }
+}
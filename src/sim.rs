@@ -0,0 +1,205 @@
+//! A simulated shared medium connecting one master and multiple slaves.
+//!
+//! The [Bus] advances in steps, each step being the time it takes to
+//! transmit a single byte. In every step, all nodes that want to transmit
+//! put their byte on the bus and every other node receives it. If multiple
+//! nodes transmit in the same step, a collision occurs: The medium behaves
+//! like an open-drain line, delivering the wired-AND of all bytes.
+//!
+//! Before every step, the nodes are [ticked](Node::tick) with the
+//! simulated time, so they can detect timeouts.
+//!
+//! The bus implements [Transport], so a [Master](crate::master::Master)
+//! can be operated on it just like on a real medium. The [chain] module
+//! simulates a chain of slaves forwarding the frames instead.
+
+pub mod chain;
+pub mod fault;
+
+#[cfg(test)]
+mod test;
+#[cfg(test)]
+mod test_support;
+
+use std::{collections::VecDeque, time::Duration};
+
+use crate::{
+    master::transport::{Timeouts, Transport, TransportError},
+    slave::transceiver::{SlaveMemory, Transceiver},
+};
+
+/// A node on the simulated [Bus]
+pub trait Node {
+    /// Processes a byte received from the bus or
+    /// polls for a byte to transmit if `rx` is `None`
+    /// # Returns
+    /// A byte to transmit on the bus, if any
+    fn handle(&mut self, rx: Option<u8>) -> Option<u8>;
+
+    /// Advances the time of the node
+    /// # Arguments
+    /// * `now` - The simulated time that has passed
+    fn tick(&mut self, now: Duration) {
+        let _ = now;
+    }
+}
+
+impl<M: SlaveMemory> Node for Transceiver<'_, M> {
+    fn handle(&mut self, rx: Option<u8>) -> Option<u8> {
+        Transceiver::handle(self, rx)
+    }
+
+    fn tick(&mut self, now: Duration) {
+        Transceiver::tick(self, now);
+    }
+}
+
+/// A node attached to the bus along with the byte it wants to transmit
+struct Attached<'a> {
+    node: &'a mut dyn Node,
+    pending: Option<u8>,
+}
+
+/// A simulated shared medium connecting a master and multiple [Node]s
+pub struct Bus<'a> {
+    nodes: Vec<Attached<'a>>,
+
+    master_tx: VecDeque<u8>,
+    master_rx: VecDeque<u8>,
+
+    step_time: Duration,
+    time: Duration,
+    collisions: usize,
+    timeouts: Timeouts,
+}
+
+impl Default for Bus<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Bus<'a> {
+    /// Creates a new bus without any nodes.
+    ///
+    /// Every step takes 1ms of simulated time by default.
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            master_tx: VecDeque::new(),
+            master_rx: VecDeque::new(),
+            step_time: Duration::from_millis(1),
+            time: Duration::ZERO,
+            collisions: 0,
+            timeouts: Timeouts::default(),
+        }
+    }
+
+    /// Attaches a `node` to the bus
+    pub fn attach(&mut self, node: &'a mut dyn Node) -> &mut Self {
+        self.nodes.push(Attached {
+            node,
+            pending: None,
+        });
+        self
+    }
+
+    /// Sets the simulated time a single step takes
+    pub fn set_step_time(&mut self, step_time: Duration) {
+        self.step_time = step_time;
+    }
+
+    /// Sets the timeouts applied by the master while waiting for responses
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = timeouts;
+    }
+
+    /// Returns the simulated time that has passed
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Returns the amount of collisions that occurred on the bus
+    pub fn collisions(&self) -> usize {
+        self.collisions
+    }
+
+    /// Advances the bus by one step
+    /// # Returns
+    /// The byte that has been on the bus during this step, if any
+    pub fn step(&mut self) -> Option<u8> {
+        self.time += self.step_time;
+
+        for a in self.nodes.iter_mut() {
+            a.node.tick(self.time);
+            if a.pending.is_none() {
+                a.pending = a.node.handle(None);
+            }
+        }
+
+        let master = self.master_tx.pop_front();
+        let mut senders = master.is_some() as usize;
+        let mut value = master.unwrap_or(0xFF);
+        let mut sent = vec![false; self.nodes.len()];
+
+        for (a, sent) in self.nodes.iter_mut().zip(sent.iter_mut()) {
+            if let Some(tx) = a.pending.take() {
+                value &= tx;
+                senders += 1;
+                *sent = true;
+            }
+        }
+
+        if senders == 0 {
+            return None;
+        }
+
+        if senders > 1 {
+            self.collisions += 1;
+        }
+
+        for (a, sent) in self.nodes.iter_mut().zip(sent) {
+            if !sent {
+                a.pending = a.node.handle(Some(value));
+            }
+        }
+
+        if master.is_none() {
+            self.master_rx.push_back(value);
+        }
+
+        Some(value)
+    }
+
+    /// Advances the bus until it stays idle for one step
+    pub fn run(&mut self) {
+        while self.step().is_some() {}
+    }
+}
+
+impl Transport for Bus<'_> {
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        self.master_rx.clear();
+        self.master_tx.extend(data);
+
+        while !self.master_tx.is_empty() {
+            self.step();
+        }
+
+        Ok(())
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<Option<u8>, TransportError> {
+        let deadline = self.time + timeout;
+
+        while self.master_rx.is_empty() && self.time < deadline {
+            self.step();
+        }
+
+        Ok(self.master_rx.pop_front())
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+}
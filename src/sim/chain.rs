@@ -0,0 +1,147 @@
+//! A simulated chain of slaves forwarding every byte on the fly.
+//!
+//! The master transmits into the first slave of the [Chain], every slave
+//! passes each byte on to the next one and the last slave returns the
+//! bytes to the master. Slaves fill in their data while the frame passes
+//! through them, so a single frame can collect data from all slaves.
+//!
+//! Every byte takes one step through the whole chain, the latency of the
+//! individual slaves is not simulated.
+
+#[cfg(test)]
+mod test;
+
+use std::{collections::VecDeque, time::Duration};
+
+use crate::{
+    master::transport::{Timeouts, Transport, TransportError},
+    slave::transceiver::{SlaveMemory, Transceiver},
+};
+
+/// A node in the simulated [Chain]
+pub trait Forwarder {
+    /// Processes a byte received from upstream
+    /// # Returns
+    /// The byte to pass downstream
+    fn forward(&mut self, rx: u8) -> u8;
+
+    /// Advances the time of the node
+    /// # Arguments
+    /// * `now` - The simulated time that has passed
+    fn tick(&mut self, now: Duration) {
+        let _ = now;
+    }
+}
+
+impl<M: SlaveMemory> Forwarder for Transceiver<'_, M> {
+    fn forward(&mut self, rx: u8) -> u8 {
+        Transceiver::forward(self, rx)
+    }
+
+    fn tick(&mut self, now: Duration) {
+        Transceiver::tick(self, now);
+    }
+}
+
+/// A simulated chain of [Forwarder]s, returning all bytes to the master
+pub struct Chain<'a> {
+    nodes: Vec<&'a mut dyn Forwarder>,
+
+    master_rx: VecDeque<u8>,
+
+    step_time: Duration,
+    time: Duration,
+    timeouts: Timeouts,
+}
+
+impl Default for Chain<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Chain<'a> {
+    /// Creates a new chain without any nodes.
+    ///
+    /// Every step takes 1ms of simulated time by default.
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            master_rx: VecDeque::new(),
+            step_time: Duration::from_millis(1),
+            time: Duration::ZERO,
+            timeouts: Timeouts::default(),
+        }
+    }
+
+    /// Appends a `node` to the end of the chain
+    pub fn attach(&mut self, node: &'a mut dyn Forwarder) -> &mut Self {
+        self.nodes.push(node);
+        self
+    }
+
+    /// Sets the simulated time a single step takes
+    pub fn set_step_time(&mut self, step_time: Duration) {
+        self.step_time = step_time;
+    }
+
+    /// Sets the timeouts applied by the master while waiting for responses
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = timeouts;
+    }
+
+    /// Returns the simulated time that has passed
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Advances the chain by one step, passing `tx` from the master through
+    /// all nodes. The byte returning to the master is queued for reception.
+    /// # Returns
+    /// The byte returning to the master, if any
+    pub fn step(&mut self, tx: Option<u8>) -> Option<u8> {
+        self.time += self.step_time;
+
+        for node in self.nodes.iter_mut() {
+            node.tick(self.time);
+        }
+
+        let mut value = tx?;
+        for node in self.nodes.iter_mut() {
+            value = node.forward(value);
+        }
+
+        self.master_rx.push_back(value);
+        Some(value)
+    }
+}
+
+impl Transport for Chain<'_> {
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        self.master_rx.clear();
+
+        for b in data {
+            self.step(Some(*b));
+        }
+
+        Ok(())
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<Option<u8>, TransportError> {
+        let deadline = self.time + timeout;
+
+        while self.master_rx.is_empty() && self.time < deadline {
+            self.step(None);
+        }
+
+        Ok(self.master_rx.pop_front())
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+
+    fn forwarding(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{
+    frame::SlaveAddress,
+    master::{transport::TransportError, Master},
+    sim::{
+        chain::{Chain, Forwarder},
+        test_support::{mac, new_slaves},
+    },
+    system::MmuEntry,
+};
+
+/// Flips a bit of every byte with the value `0xAB`
+struct Corrupter;
+
+impl Forwarder for Corrupter {
+    fn forward(&mut self, rx: u8) -> u8 {
+        if rx == 0xAB {
+            rx ^ 0x01
+        } else {
+            rx
+        }
+    }
+}
+
+#[test]
+fn physical_memory() {
+    new_slaves!(s0: mac(0), s1: mac(1), s2: mac(2));
+    s1.memory().data = [1, 2, 3, 4];
+
+    let mut chain = Chain::new();
+    chain.attach(&mut s0).attach(&mut s1).attach(&mut s2);
+    let mut master = Master::new(chain);
+    master.sync().unwrap();
+
+    master
+        .write(SlaveAddress::Physical(mac(2)), 0, &[5, 6, 7, 8])
+        .unwrap();
+
+    for (i, expected) in [(0, [0; 4]), (1, [1, 2, 3, 4]), (2, [5, 6, 7, 8])] {
+        let data = master.read(SlaveAddress::Physical(mac(i)), 0, 4).unwrap();
+        assert_eq!(data, expected);
+    }
+
+    drop(master);
+    for s in [&s0, &s1, &s2] {
+        assert!(s.in_sync());
+        assert_eq!(s.diagnostics().frame_crc_errors, 0);
+    }
+}
+
+#[test]
+fn logical_memory() {
+    new_slaves!(s0: mac(0), s1: mac(1), s2: mac(2));
+    s0.memory().data = [0x10, 0x11, 0x12, 0x13];
+    s1.memory().data = [0x20, 0x21, 0x22, 0x23];
+    s2.memory().data = [0x30, 0x31, 0x32, 0x33];
+
+    let mut chain = Chain::new();
+    chain.attach(&mut s0).attach(&mut s1).attach(&mut s2);
+    let mut master = Master::new(chain);
+    master.sync().unwrap();
+
+    for (i, logical_start) in [(0, 0x100), (1, 0x102), (2, 0x104)] {
+        let entry = MmuEntry {
+            logical_start,
+            length: 2,
+            physical_start: 2,
+            read: true,
+            write: true,
+        };
+        master
+            .configure_mmu(SlaveAddress::Physical(mac(i)), 0, Some(entry))
+            .unwrap();
+    }
+
+    // A single frame collects the data of all slaves
+    let data = master.read(SlaveAddress::LogicalMemory, 0x100, 6).unwrap();
+    assert_eq!(data, vec![0x12, 0x13, 0x22, 0x23, 0x32, 0x33]);
+
+    drop(master);
+    for s in [&s0, &s1, &s2] {
+        assert!(s.in_sync());
+        assert_eq!(s.diagnostics().frame_crc_errors, 0);
+    }
+}
+
+#[test]
+fn position() {
+    new_slaves!(s0: mac(0), s1: mac(0), s2: mac(0));
+
+    let mut chain = Chain::new();
+    chain.attach(&mut s0).attach(&mut s1).attach(&mut s2);
+    let mut master = Master::new(chain);
+    master.sync().unwrap();
+
+    // Identical slaves can be told apart by their positions
+    for i in 0..3 {
+        master
+            .write(SlaveAddress::Position(i), 0, &[i as u8; 4])
+            .unwrap();
+    }
+
+    for i in 0..3 {
+        let data = master.read(SlaveAddress::Position(i), 0, 4).unwrap();
+        assert_eq!(data, [i as u8; 4]);
+    }
+
+    drop(master);
+    assert_eq!(s0.memory().data, [0; 4]);
+    assert_eq!(s1.memory().data, [1; 4]);
+    assert_eq!(s2.memory().data, [2; 4]);
+}
+
+#[test]
+fn working_counter() {
+    new_slaves!(s0: mac(0), s1: mac(1), s2: mac(2));
+    for (i, s) in [&mut s0, &mut s1, &mut s2].into_iter().enumerate() {
+        s.memory().data = [i as u8; 4];
+    }
+
+    let mut chain = Chain::new();
+    chain.attach(&mut s0).attach(&mut s1).attach(&mut s2);
+    let mut master = Master::new(chain);
+    master.sync().unwrap();
+
+    // All slaves count in the same slot
+    master.configure_working_counter(1).unwrap();
+
+    master
+        .write_acknowledged(SlaveAddress::Broadcast, 3, &[0xA], 3)
+        .unwrap();
+    master
+        .write_acknowledged(SlaveAddress::Position(1), 2, &[0xB], 1)
+        .unwrap();
+
+    let data = master.read(SlaveAddress::Physical(mac(2)), 0, 4).unwrap();
+    assert_eq!(data, vec![2, 2, 2, 0xA]);
+    assert_eq!(master.working_counter(), Some(1));
+
+    let data = master.read(SlaveAddress::Physical(mac(3)), 0, 4).unwrap();
+    assert_eq!(data, vec![0; 4]);
+    assert_eq!(master.working_counter(), Some(0));
+
+    drop(master);
+    assert_eq!(s1.memory().data, [1, 1, 0xB, 0xA]);
+}
+
+#[test]
+fn broadcast_read() {
+    new_slaves!(s0: mac(0), s1: mac(1), s2: mac(2));
+    s0.memory().data = [0x01, 0x10, 0, 0];
+    s1.memory().data = [0x02, 0x20, 0, 0];
+    s2.memory().data = [0x04, 0x40, 0, 0x80];
+
+    let mut chain = Chain::new();
+    chain.attach(&mut s0).attach(&mut s1).attach(&mut s2);
+    let mut master = Master::new(chain);
+    master.sync().unwrap();
+    master.configure_working_counter(1).unwrap();
+
+    // The master receives the bitwise OR of the data of all slaves
+    let data = master.read(SlaveAddress::Broadcast, 0, 4).unwrap();
+    assert_eq!(data, vec![0x07, 0x70, 0, 0x80]);
+    assert_eq!(master.working_counter(), Some(3));
+}
+
+#[test]
+fn scan() {
+    new_slaves!(s0: mac(7), s1: mac(3), s2: mac(5));
+
+    let mut chain = Chain::new();
+    chain.attach(&mut s0).attach(&mut s1).attach(&mut s2);
+    let mut master = Master::new(chain);
+    master.sync().unwrap();
+
+    assert_eq!(master.scan().unwrap(), vec![mac(3), mac(5), mac(7)]);
+}
+
+#[test]
+fn corruption() {
+    new_slaves!(s0: mac(0), s1: mac(1));
+    s0.memory().data = [0xAB; 4];
+    let mut corrupter = Corrupter;
+
+    let mut chain = Chain::new();
+    chain.attach(&mut s0).attach(&mut corrupter).attach(&mut s1);
+    let mut master = Master::new(chain);
+    master.sync().unwrap();
+
+    // The data is corrupted after the first slave filled it in, which
+    // neither the slave downstream nor the master must accept
+    let res = master.read(SlaveAddress::Physical(mac(0)), 0, 4);
+    assert!(
+        matches!(res, Err(TransportError::Protocol(_))),
+        "Expected a protocol error, got {res:?}"
+    );
+
+    drop(master);
+    assert!(s0.in_sync());
+    assert!(!s1.in_sync());
+    assert_eq!(s1.diagnostics().frame_crc_errors, 1);
+}
@@ -0,0 +1,374 @@
+//! Fault injection for byte streams on the bus.
+//!
+//! The [FaultInjector] corrupts byte streams using deterministic, seeded
+//! randomness, so failing runs can be reproduced. It can be placed in front
+//! of a single [Node] using a [FaultyNode] or around a master [Transport]
+//! using a [FaultyTransport], which also reports how the transfers ended.
+
+#[cfg(test)]
+mod test;
+
+use std::{collections::VecDeque, time::Duration};
+
+use crate::{
+    frame::Frame,
+    master::{
+        transceiver::{Response, Transceiver},
+        transport::{self, Timeouts, Transport, TransportError},
+    },
+    sim::Node,
+};
+
+/// The probabilities of the individual faults, each in the range `0.0..=1.0`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaultConfig {
+    /// The seed for the random number generator
+    pub seed: u64,
+
+    /// The probability of a single bit of a byte being flipped
+    pub bit_flip: f64,
+
+    /// The probability of a byte being dropped
+    pub drop: f64,
+
+    /// The probability of a byte being duplicated
+    pub duplicate: f64,
+
+    /// The probability of a garbage byte being inserted after a byte
+    pub garbage: f64,
+
+    /// The probability of a frame being truncated at a random position
+    pub truncate: f64,
+}
+
+impl Default for FaultConfig {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            bit_flip: 0.0,
+            drop: 0.0,
+            duplicate: 0.0,
+            garbage: 0.0,
+            truncate: 0.0,
+        }
+    }
+}
+
+/// A xorshift64* random number generator
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // The state of xorshift must never be 0
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns `true` with the `probability`
+    fn chance(&mut self, probability: f64) -> bool {
+        let value = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        probability > 0.0 && value < probability
+    }
+
+    /// Returns a random value below `max`
+    fn below(&mut self, max: usize) -> usize {
+        (self.next() % max as u64) as usize
+    }
+}
+
+/// Injects faults into byte streams
+pub struct FaultInjector {
+    config: FaultConfig,
+    rng: Rng,
+    faults: usize,
+}
+
+impl FaultInjector {
+    /// Creates a new fault injector
+    /// # Arguments
+    /// * `config` - The probabilities of the faults and the seed
+    pub fn new(config: FaultConfig) -> Self {
+        Self {
+            rng: Rng::new(config.seed),
+            config,
+            faults: 0,
+        }
+    }
+
+    /// Changes the probabilities of the faults without reseeding
+    pub fn set_config(&mut self, config: FaultConfig) {
+        self.config = config;
+    }
+
+    /// Returns the amount of faults that have been injected
+    pub fn faults(&self) -> usize {
+        self.faults
+    }
+
+    /// Injects faults into a single byte of a stream
+    /// # Arguments
+    /// * `byte` - The byte to corrupt
+    /// * `out` - The bytes to transmit instead of `byte`
+    /// # Returns
+    /// Whether a fault has been injected
+    pub fn inject_byte(&mut self, byte: u8, out: &mut Vec<u8>) -> bool {
+        let faults = self.faults;
+
+        if self.rng.chance(self.config.drop) {
+            self.faults += 1;
+            return true;
+        }
+
+        let mut byte = byte;
+        if self.rng.chance(self.config.bit_flip) {
+            byte ^= 1 << self.rng.below(8);
+            self.faults += 1;
+        }
+        out.push(byte);
+
+        if self.rng.chance(self.config.duplicate) {
+            out.push(byte);
+            self.faults += 1;
+        }
+
+        if self.rng.chance(self.config.garbage) {
+            out.push(self.rng.next() as u8);
+            self.faults += 1;
+        }
+
+        self.faults != faults
+    }
+
+    /// Injects faults into a complete frame, which can also be truncated
+    /// # Arguments
+    /// * `frame` - The frame to corrupt
+    /// * `out` - The bytes to transmit instead of `frame`
+    /// # Returns
+    /// Whether a fault has been injected
+    pub fn inject_frame(&mut self, frame: &[u8], out: &mut Vec<u8>) -> bool {
+        let mut faulted = false;
+
+        let mut len = frame.len();
+        if !frame.is_empty() && self.rng.chance(self.config.truncate) {
+            len = self.rng.below(frame.len());
+            self.faults += 1;
+            faulted = true;
+        }
+
+        for b in &frame[..len] {
+            faulted |= self.inject_byte(*b, out);
+        }
+
+        faulted
+    }
+}
+
+/// A [Node] that receives its bytes through a [FaultInjector]
+pub struct FaultyNode<N: Node> {
+    node: N,
+    injector: FaultInjector,
+    tx: VecDeque<u8>,
+}
+
+impl<N: Node> FaultyNode<N> {
+    /// Creates a new faulty node
+    /// # Arguments
+    /// * `node` - The node to inject the faults for
+    /// * `config` - The probabilities of the faults and the seed
+    pub fn new(node: N, config: FaultConfig) -> Self {
+        Self {
+            node,
+            injector: FaultInjector::new(config),
+            tx: VecDeque::new(),
+        }
+    }
+
+    /// Returns the underlying node
+    pub fn node(&mut self) -> &mut N {
+        &mut self.node
+    }
+
+    /// Returns the fault injector
+    pub fn injector(&mut self) -> &mut FaultInjector {
+        &mut self.injector
+    }
+}
+
+impl<N: Node> Node for FaultyNode<N> {
+    fn handle(&mut self, rx: Option<u8>) -> Option<u8> {
+        match rx {
+            Some(rx) => {
+                let mut bytes = Vec::new();
+                self.injector.inject_byte(rx, &mut bytes);
+
+                // If the node answers to duplicated or garbage bytes,
+                // the answers are transmitted in the following steps
+                for b in bytes {
+                    self.tx.extend(self.node.handle(Some(b)));
+                }
+            }
+            None => self.tx.extend(self.node.handle(None)),
+        }
+
+        self.tx.pop_front()
+    }
+
+    fn tick(&mut self, now: Duration) {
+        self.node.tick(now);
+    }
+}
+
+/// The outcome of the transfers over a [FaultyTransport]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultReport {
+    /// The amount of transfers
+    pub transfers: usize,
+
+    /// The amount of transfers a fault has been injected into
+    pub corrupted: usize,
+
+    /// The amount of transfers that failed
+    pub lost: usize,
+
+    /// The amount of corrupted transfers that returned data which differs
+    /// from the data of a fault-free repetition of the transfer, meaning
+    /// the corruption has not been detected by the CRC
+    pub undetected: usize,
+
+    /// The amount of corrupted transfers without a response that
+    /// completed. The master cannot tell if the slaves accepted them.
+    pub unconfirmed: usize,
+}
+
+/// A [Transport] that injects faults into all bytes sent and received
+pub struct FaultyTransport<T: Transport> {
+    transport: T,
+    injector: FaultInjector,
+    rx: VecDeque<u8>,
+    faulted: bool,
+    bypass: bool,
+    report: FaultReport,
+}
+
+impl<T: Transport> FaultyTransport<T> {
+    /// Creates a new faulty transport
+    /// # Arguments
+    /// * `transport` - The transport to inject the faults into
+    /// * `config` - The probabilities of the faults and the seed
+    pub fn new(transport: T, config: FaultConfig) -> Self {
+        Self {
+            transport,
+            injector: FaultInjector::new(config),
+            rx: VecDeque::new(),
+            faulted: false,
+            bypass: false,
+            report: FaultReport::default(),
+        }
+    }
+
+    /// Returns the underlying transport
+    pub fn transport(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Returns the fault injector
+    pub fn injector(&mut self) -> &mut FaultInjector {
+        &mut self.injector
+    }
+
+    /// Returns the outcome of the transfers so far
+    pub fn report(&self) -> FaultReport {
+        self.report
+    }
+
+    /// Repeats the transfer of `frame` without injecting faults
+    /// # Returns
+    /// The data of the response, if the repetition returned any
+    fn reference(&mut self, transceiver: &mut Transceiver, frame: &Frame) -> Option<Vec<u8>> {
+        self.bypass = true;
+        let res = transport::transfer(self, transceiver, frame);
+        self.bypass = false;
+
+        match res {
+            Ok(Response::Data(data)) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Transport> Transport for FaultyTransport<T> {
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        self.rx.clear();
+        if self.bypass {
+            return self.transport.send(data);
+        }
+
+        let mut out = Vec::new();
+        self.faulted |= self.injector.inject_frame(data, &mut out);
+        self.transport.send(&out)
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<Option<u8>, TransportError> {
+        if self.bypass {
+            return self.transport.receive(timeout);
+        }
+
+        while self.rx.is_empty() {
+            let Some(rx) = self.transport.receive(timeout)? else {
+                return Ok(None);
+            };
+
+            let mut out = Vec::new();
+            self.faulted |= self.injector.inject_byte(rx, &mut out);
+            self.rx.extend(out);
+        }
+
+        Ok(self.rx.pop_front())
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        self.transport.timeouts()
+    }
+
+    fn forwarding(&self) -> bool {
+        self.transport.forwarding()
+    }
+
+    fn transfer(
+        &mut self,
+        transceiver: &mut Transceiver,
+        frame: &Frame,
+    ) -> Result<Response, TransportError> {
+        self.faulted = false;
+        let res = transport::transfer(self, transceiver, frame);
+
+        self.report.transfers += 1;
+        if self.faulted {
+            self.report.corrupted += 1;
+        }
+
+        match &res {
+            Err(_) => self.report.lost += 1,
+            Ok(Response::Data(data)) if self.faulted => {
+                // Reads have no side effects, so they can be repeated
+                // to find out whether the data has been corrupted
+                if self
+                    .reference(transceiver, frame)
+                    .is_some_and(|reference| reference != *data)
+                {
+                    self.report.undetected += 1;
+                }
+            }
+            Ok(Response::None) if self.faulted => self.report.unconfirmed += 1,
+            Ok(_) => {}
+        }
+
+        res
+    }
+}
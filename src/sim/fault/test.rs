@@ -0,0 +1,145 @@
+use crate::{
+    frame::SlaveAddress,
+    master::Master,
+    sim::{
+        fault::{FaultConfig, FaultInjector, FaultyNode, FaultyTransport},
+        test_support::{mac, new_slaves, Regs},
+        Bus, Node,
+    },
+    slave::transceiver::Transceiver,
+};
+
+const NOISE: FaultConfig = FaultConfig {
+    seed: 0x5eed,
+    bit_flip: 0.01,
+    drop: 0.005,
+    duplicate: 0.005,
+    garbage: 0.005,
+    truncate: 0.02,
+};
+
+#[test]
+fn deterministic() {
+    let data: Vec<u8> = (0..=255).collect();
+
+    let run = |config| {
+        let mut injector = FaultInjector::new(config);
+        let mut out = Vec::new();
+        injector.inject_frame(&data, &mut out);
+        (out, injector.faults())
+    };
+
+    let (clean, faults) = run(FaultConfig::default());
+    assert_eq!(clean, data);
+    assert_eq!(faults, 0);
+
+    let (a, faults) = run(NOISE);
+    let (b, _) = run(NOISE);
+    assert_eq!(a, b);
+    assert!(faults > 0);
+    assert_ne!(a, data);
+
+    let (c, _) = run(FaultConfig { seed: 42, ..NOISE });
+    assert_ne!(a, c);
+}
+
+#[test]
+fn faulty_node() {
+    new_slaves!(s1: mac(1));
+    let mut scratchpad = [0u8; 16];
+    let s0 = Transceiver::with_memory(&mut scratchpad, mac(0), Regs::default());
+    let mut s0 = FaultyNode::new(
+        s0,
+        FaultConfig {
+            bit_flip: 1.0,
+            ..Default::default()
+        },
+    );
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0).attach(&mut s1);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+    master
+        .write(SlaveAddress::Broadcast, 0, &7u32.to_le_bytes())
+        .unwrap();
+    drop(master);
+
+    // Only the faulty node sees the corrupted frames
+    assert!(!s0.node().in_sync());
+    // The faulty node never sees a valid frame
+    assert_eq!(s0.node().memory().data, [0; 4]);
+    assert!(s1.in_sync());
+    assert_eq!(s1.memory().data, 7u32.to_le_bytes());
+}
+
+/// A node that answers every byte with the byte itself
+struct Echo;
+
+impl Node for Echo {
+    fn handle(&mut self, rx: Option<u8>) -> Option<u8> {
+        rx
+    }
+}
+
+#[test]
+fn faulty_node_duplicates() {
+    let mut node = FaultyNode::new(
+        Echo,
+        FaultConfig {
+            duplicate: 1.0,
+            ..Default::default()
+        },
+    );
+
+    // The answers to both bytes are transmitted one after the other
+    assert_eq!(node.handle(Some(0x12)), Some(0x12));
+    assert_eq!(node.handle(None), Some(0x12));
+    assert_eq!(node.handle(None), None);
+    assert_eq!(node.injector().faults(), 1);
+}
+
+#[test]
+fn recovery() {
+    new_slaves!(s0: mac(0), s1: mac(1));
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0).attach(&mut s1);
+    let mut master = Master::new(FaultyTransport::new(bus, NOISE));
+
+    let mut synced = false;
+    for i in 0u32..200 {
+        if !synced {
+            synced = master.sync().is_ok();
+            continue;
+        }
+
+        let address = SlaveAddress::Physical(mac(i as u8 % 2));
+        let ok = master.write(address.clone(), 0, &i.to_le_bytes()).is_ok()
+            && master.read(address, 0, 4).is_ok();
+        synced = ok;
+    }
+
+    let report = master.transport().report();
+    assert!(report.lost > 0, "{report:?}");
+    assert!(report.corrupted >= report.lost, "{report:?}");
+    assert!(report.transfers >= 200, "{report:?}");
+    // The CRCs detect all corrupted responses
+    assert_eq!(report.undetected, 0, "{report:?}");
+
+    // Without noise, both slaves recover with a single sync
+    master
+        .transport()
+        .injector()
+        .set_config(FaultConfig::default());
+    master.sync().unwrap();
+    for i in 0..2 {
+        let address = SlaveAddress::Physical(mac(i));
+        master.write(address.clone(), 0, &[i, 0, 0, 0]).unwrap();
+        assert_eq!(master.read(address, 0, 4).unwrap(), vec![i, 0, 0, 0]);
+    }
+
+    drop(master);
+    assert!(s0.in_sync());
+    assert!(s1.in_sync());
+}
@@ -0,0 +1,395 @@
+use std::time::Duration;
+
+use crate::{
+    frame::SlaveAddress,
+    master::{
+        transceiver::Error,
+        transport::{Transport, TransportError},
+        Master,
+    },
+    sim::{
+        test_support::{mac, new_slaves},
+        Bus,
+    },
+    slave::transceiver::Timeouts,
+    system::{Diagnostics, MmuEntry, MMU_COUNT},
+    START_BYTE,
+};
+
+#[test]
+fn addressing() {
+    new_slaves!(s0: mac(0), s1: mac(1), s2: mac(2));
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0).attach(&mut s1).attach(&mut s2);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+
+    master
+        .write(SlaveAddress::Broadcast, 0, &[1, 1, 1, 1])
+        .unwrap();
+    master
+        .write(SlaveAddress::Physical(mac(1)), 1, &[2, 3])
+        .unwrap();
+
+    master.assign_logical_address(mac(2), [0, 2]).unwrap();
+    master
+        .write(SlaveAddress::Logical([0, 2]), 3, &[4])
+        .unwrap();
+
+    let data = master.read(SlaveAddress::Physical(mac(1)), 0, 4).unwrap();
+    assert_eq!(data, vec![1, 2, 3, 1]);
+    assert_eq!(master.transport().collisions(), 0);
+
+    drop(master);
+    for s in [&s0, &s1, &s2] {
+        assert!(s.in_sync());
+    }
+    assert_eq!(s0.memory().data, [1, 1, 1, 1]);
+    assert_eq!(s1.memory().data, [1, 2, 3, 1]);
+    assert_eq!(s2.memory().data, [1, 1, 1, 4]);
+}
+
+#[test]
+fn logical_memory() {
+    new_slaves!(s0: mac(0), s1: mac(1));
+    s0.memory().data = [0x10, 0x11, 0x12, 0x13];
+    s1.memory().data = [0x20, 0x21, 0x22, 0x23];
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0).attach(&mut s1);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+
+    for (i, logical_start) in [(0, 0x100), (1, 0x102)] {
+        let entry = MmuEntry {
+            logical_start,
+            length: 2,
+            physical_start: 2,
+            read: true,
+            write: true,
+        };
+        master
+            .configure_mmu(SlaveAddress::Physical(mac(i)), 0, Some(entry))
+            .unwrap();
+    }
+
+    // The slaves only have a limited amount of MMU entries
+    let res = master.configure_mmu(SlaveAddress::Physical(mac(0)), MMU_COUNT, None);
+    assert!(
+        matches!(res, Err(TransportError::Protocol(Error::InvalidIndex))),
+        "Expected an invalid index, got {res:?}"
+    );
+
+    let data = master.read(SlaveAddress::LogicalMemory, 0x100, 4).unwrap();
+    assert_eq!(data, vec![0x12, 0x13, 0x22, 0x23]);
+
+    master
+        .write(SlaveAddress::LogicalMemory, 0x101, &[0xA, 0xB])
+        .unwrap();
+    assert_eq!(master.transport().collisions(), 0);
+
+    drop(master);
+    assert_eq!(s0.memory().data, [0x10, 0x11, 0x12, 0x0A]);
+    assert_eq!(s1.memory().data, [0x20, 0x21, 0x0B, 0x23]);
+}
+
+#[test]
+fn collision() {
+    // Two slaves with the same physical address
+    new_slaves!(s0: mac(1), s1: mac(1));
+    s0.memory().data = [0x0F; 4];
+    s1.memory().data = [0xF0; 4];
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0).attach(&mut s1);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+
+    let res = master.read(SlaveAddress::Physical(mac(1)), 0, 4);
+    assert!(
+        matches!(res, Err(TransportError::Protocol(_))),
+        "Expected a protocol error, got {res:?}"
+    );
+    assert!(master.transport().collisions() > 0);
+}
+
+#[test]
+fn timeout() {
+    new_slaves!(s0: mac(0));
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+
+    let res = master.read(SlaveAddress::Physical(mac(1)), 0, 4);
+    assert!(matches!(res, Err(TransportError::Timeout)), "{res:?}");
+
+    // The slave that has not been targeted is still in sync
+    drop(master);
+    assert!(s0.in_sync());
+}
+
+#[test]
+fn diagnostics() {
+    new_slaves!(s0: mac(0));
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+    master.write(SlaveAddress::Broadcast, 0, &[1, 2]).unwrap();
+
+    let diagnostics = master
+        .read_diagnostics(SlaveAddress::Physical(mac(0)))
+        .unwrap();
+    assert_eq!(
+        diagnostics,
+        Diagnostics {
+            valid_frames: 2,
+            sync_gained: 1,
+            ..Default::default()
+        }
+    );
+
+    drop(master);
+    assert_eq!(s0.diagnostics().valid_frames, 3);
+}
+
+#[test]
+fn truncated_frame() {
+    new_slaves!(s0: mac(0));
+    s0.memory().data = [1, 2, 3, 4];
+    s0.set_timeouts(Timeouts {
+        inter_byte: Some(Duration::from_millis(5)),
+        frame: None,
+        ack_slot: None,
+    });
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+
+    // A frame that is cut off right after its start byte
+    master.transport().send(&[START_BYTE]).unwrap();
+    for _ in 0..10 {
+        master.transport().step();
+    }
+
+    let data = master.read(SlaveAddress::Physical(mac(0)), 0, 4).unwrap();
+    assert_eq!(data, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn watchdog() {
+    new_slaves!(s0: mac(0));
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+    master
+        .configure_watchdog(
+            SlaveAddress::Physical(mac(0)),
+            Some(Duration::from_millis(50)),
+        )
+        .unwrap();
+
+    // Polling the slave keeps it in sync
+    for _ in 0..10 {
+        master.read(SlaveAddress::Physical(mac(0)), 0, 4).unwrap();
+        for _ in 0..20 {
+            master.transport().step();
+        }
+    }
+
+    for _ in 0..60 {
+        master.transport().step();
+    }
+    let res = master.read(SlaveAddress::Physical(mac(0)), 0, 4);
+    assert!(matches!(res, Err(TransportError::Timeout)), "{res:?}");
+
+    drop(master);
+    assert!(!s0.in_sync());
+    assert_eq!(s0.diagnostics().sync_lost, 1);
+}
+
+#[test]
+fn watchdog_limited() {
+    new_slaves!(s0: mac(0));
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+    master
+        .configure_watchdog(
+            SlaveAddress::Physical(mac(0)),
+            Some(Duration::from_secs(70)),
+        )
+        .unwrap();
+
+    drop(master);
+    assert_eq!(s0.watchdog(), Some(Duration::from_millis(u16::MAX as u64)));
+}
+
+#[test]
+fn working_counter() {
+    new_slaves!(s0: mac(0), s1: mac(1), s2: mac(2));
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0).attach(&mut s1).attach(&mut s2);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+
+    // Without the working counter, writes cannot be acknowledged
+    let res = master.write_acknowledged(SlaveAddress::Broadcast, 0, &[1], 3);
+    assert!(
+        matches!(
+            res,
+            Err(TransportError::Protocol(Error::WorkingCounterDisabled))
+        ),
+        "Expected a disabled working counter, got {res:?}"
+    );
+
+    for i in 0..3 {
+        master
+            .assign_ack_slot(SlaveAddress::Physical(mac(i)), i)
+            .unwrap();
+    }
+    master.configure_working_counter(3).unwrap();
+
+    master
+        .write_acknowledged(SlaveAddress::Broadcast, 0, &[1], 3)
+        .unwrap();
+    master
+        .write_acknowledged(SlaveAddress::Physical(mac(1)), 0, &[2], 1)
+        .unwrap();
+
+    // A write to a missing slave is no longer mistaken for a successful one
+    let res = master.write_acknowledged(SlaveAddress::Physical(mac(3)), 0, &[3], 1);
+    assert!(
+        matches!(
+            res,
+            Err(TransportError::Protocol(Error::WorkingCounter {
+                expected: 1,
+                received: 0
+            }))
+        ),
+        "{res:?}"
+    );
+
+    let data = master.read(SlaveAddress::Physical(mac(2)), 0, 1).unwrap();
+    assert_eq!(data, vec![1]);
+    assert_eq!(master.working_counter(), Some(1));
+    assert_eq!(master.transport().collisions(), 0);
+
+    master.reset().unwrap();
+    master.write(SlaveAddress::Broadcast, 0, &[4]).unwrap();
+    assert_eq!(master.working_counter(), None);
+}
+
+#[test]
+fn working_counter_missing_slave() {
+    new_slaves!(s0: mac(0), s2: mac(2), s3: mac(3));
+    for s in [&mut s0, &mut s2, &mut s3] {
+        s.set_timeouts(Timeouts {
+            ack_slot: Some(Duration::from_millis(2)),
+            ..Default::default()
+        });
+    }
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0).attach(&mut s2).attach(&mut s3);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+
+    for i in [0, 2, 3] {
+        master
+            .assign_ack_slot(SlaveAddress::Physical(mac(i)), i)
+            .unwrap();
+    }
+    master.configure_working_counter(5).unwrap();
+
+    // The slaves skip the empty slots of the missing slaves
+    master.write(SlaveAddress::Broadcast, 0, &[1]).unwrap();
+    assert_eq!(master.working_counter(), Some(3));
+
+    master
+        .write_acknowledged(SlaveAddress::Broadcast, 1, &[2], 3)
+        .unwrap();
+    let data = master.read(SlaveAddress::Physical(mac(3)), 0, 2).unwrap();
+    assert_eq!(data, vec![1, 2]);
+    assert_eq!(master.working_counter(), Some(1));
+
+    drop(master);
+    for s in [&s0, &s2, &s3] {
+        assert!(s.in_sync());
+    }
+}
+
+#[test]
+fn scan() {
+    let macs = [
+        [0x00, 0x11, 0x22, 0x33, 0x44, 0x55],
+        [0x00, 0x11, 0x22, 0x33, 0x44, 0x54],
+        [0xF0, 0, 0, 0, 0, 1],
+    ];
+    new_slaves!(s0: macs[2], s1: macs[0], s2: macs[1]);
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0).attach(&mut s1).attach(&mut s2);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+
+    assert!(master.search([0; 6], 0).unwrap());
+    assert!(!master.search([0x80, 0, 0, 0, 0, 0], 2).unwrap());
+
+    let found = master.scan().unwrap();
+    assert_eq!(found, vec![macs[1], macs[0], macs[2]]);
+
+    // The found slaves can be addressed right away
+    for mac in found {
+        master.read(SlaveAddress::Physical(mac), 0, 4).unwrap();
+    }
+}
+
+#[test]
+fn status_read() {
+    new_slaves!(s0: mac(0), s1: mac(1), s2: mac(2));
+    for (i, s) in [&mut s0, &mut s1, &mut s2].into_iter().enumerate() {
+        s.memory().data = [i as u8 + 1; 4];
+        s.set_status_read(true);
+    }
+
+    let mut bus = Bus::new();
+    bus.attach(&mut s0).attach(&mut s1).attach(&mut s2);
+    let mut master = Master::new(bus);
+    master.sync().unwrap();
+
+    // The slot of the logical address 3 is left empty
+    for (i, logical) in [(0, 1), (1, 2), (2, 4)] {
+        master.assign_logical_address(mac(i), [logical, 0]).unwrap();
+    }
+
+    // Every response of 5 bytes is followed by a gap of 2 steps
+    let status = master
+        .read_status([1, 0], 4, Duration::from_millis(7), 1, 2)
+        .unwrap();
+    let status: Vec<_> = status.into_iter().map(|s| (s.logical, s.data)).collect();
+    assert_eq!(
+        status,
+        vec![
+            ([1, 0], vec![1, 1]),
+            ([2, 0], vec![2, 2]),
+            ([4, 0], vec![3, 3])
+        ]
+    );
+    assert_eq!(master.transport().collisions(), 0);
+
+    // The slaves are back to normal operation after the slots
+    let data = master.read(SlaveAddress::Logical([4, 0]), 0, 4).unwrap();
+    assert_eq!(data, vec![3; 4]);
+}
@@ -0,0 +1,30 @@
+//! Fixtures shared by the tests of the simulator
+
+use sondbus_derive::SondbusMemory;
+
+/// The memory of the simulated slaves
+#[derive(SondbusMemory, Default)]
+pub(crate) struct Regs {
+    pub data: [u8; 4],
+}
+
+/// Returns the physical address of the `i`-th slave
+pub(crate) fn mac(i: u8) -> [u8; 6] {
+    [0, 0, 0, 0, 0, i]
+}
+
+/// Create new slaves operating on [Regs] with the given physical addresses
+macro_rules! new_slaves {
+    ($($s: ident: $mac: expr),*) => {
+        $(
+            let mut scratchpad = [0u8; 32];
+            let mut $s = crate::slave::transceiver::Transceiver::with_memory(
+                &mut scratchpad,
+                $mac,
+                crate::sim::test_support::Regs::default(),
+            );
+        )*
+    };
+}
+
+pub(crate) use new_slaves;
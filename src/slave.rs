@@ -1,3 +1,6 @@
 //! The implementation of a slave in the sondbus system
 
+pub mod memory_map;
 pub mod transceiver;
+#[cfg(feature = "udp")]
+pub mod udp;
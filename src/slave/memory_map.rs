@@ -0,0 +1,274 @@
+//! The memory map lays out the memory area of a slave as a set of regions.
+//!
+//! Each region is either backed by a buffer or by a handler and restricts
+//! the accesses to it. Accesses spanning multiple regions are split up and
+//! dispatched to the individual regions. Accesses that are not allowed are
+//! rejected before any memory is touched.
+
+#[cfg(test)]
+mod test;
+
+use crate::slave::transceiver::{Callback, CallbackAction, MemoryError, SlaveMemory};
+
+/// The accesses that are allowed to a region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// The region can only be read
+    ReadOnly,
+
+    /// The region can only be written
+    WriteOnly,
+
+    /// The region can be read and written
+    ReadWrite,
+}
+
+impl Access {
+    /// Returns whether reads or `write`s are allowed
+    pub fn allows(&self, write: bool) -> bool {
+        match self {
+            Self::ReadOnly => !write,
+            Self::WriteOnly => write,
+            Self::ReadWrite => true,
+        }
+    }
+}
+
+/// The errors that can occur while building a [MemoryMap]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapError {
+    /// There is no space for another region
+    Full,
+
+    /// The region overlaps with an already registered region
+    Overlap,
+
+    /// The region exceeds the memory area of the slave
+    OutOfRange,
+}
+
+/// The memory backing a region
+enum Backing<'a> {
+    /// The region is backed by a buffer
+    Buffer(&'a mut [u8]),
+
+    /// The region is backed by a handler, called with
+    /// offsets relative to the start of the region
+    Handler { len: u16, handler: Callback },
+}
+
+/// A region in the [MemoryMap]
+struct Region<'a> {
+    offset: u16,
+    access: Access,
+    backing: Backing<'a>,
+}
+
+impl Region<'_> {
+    fn len(&self) -> u16 {
+        match &self.backing {
+            Backing::Buffer(buf) => buf.len() as u16,
+            Backing::Handler { len, .. } => *len,
+        }
+    }
+
+    fn end(&self) -> u32 {
+        self.offset as u32 + self.len() as u32
+    }
+
+    fn contains(&self, offset: u32) -> bool {
+        offset >= self.offset as u32 && offset < self.end()
+    }
+}
+
+/// A [SlaveMemory] made up of up to `N` regions
+pub struct MemoryMap<'a, const N: usize> {
+    regions: [Option<Region<'a>>; N],
+}
+
+impl<const N: usize> Default for MemoryMap<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const N: usize> MemoryMap<'a, N> {
+    /// Creates a new memory map without any regions
+    pub const fn new() -> Self {
+        Self {
+            regions: [const { None }; N],
+        }
+    }
+
+    /// Registers a region that is backed by `buffer`
+    /// # Arguments
+    /// * `offset` - The offset of the region in the memory area
+    /// * `access` - The accesses that are allowed to the region
+    /// * `buffer` - The buffer backing the region
+    pub fn buffer(
+        &mut self,
+        offset: u16,
+        access: Access,
+        buffer: &'a mut [u8],
+    ) -> Result<&mut Self, MapError> {
+        if buffer.len() > u16::MAX as usize {
+            return Err(MapError::OutOfRange);
+        }
+
+        self.insert(Region {
+            offset,
+            access,
+            backing: Backing::Buffer(buffer),
+        })
+    }
+
+    /// Registers a region that is backed by a `handler`.
+    ///
+    /// The handler is called with offsets relative to the start of the region
+    /// and receives a [CallbackAction::Reset] if the slave is reset.
+    /// # Arguments
+    /// * `offset` - The offset of the region in the memory area
+    /// * `len` - The length of the region
+    /// * `access` - The accesses that are allowed to the region
+    /// * `handler` - The handler backing the region
+    pub fn handler(
+        &mut self,
+        offset: u16,
+        len: u16,
+        access: Access,
+        handler: Callback,
+    ) -> Result<&mut Self, MapError> {
+        self.insert(Region {
+            offset,
+            access,
+            backing: Backing::Handler { len, handler },
+        })
+    }
+
+    fn insert(&mut self, region: Region<'a>) -> Result<&mut Self, MapError> {
+        if region.end() > u16::MAX as u32 + 1 {
+            return Err(MapError::OutOfRange);
+        }
+
+        let overlaps = self
+            .regions
+            .iter()
+            .flatten()
+            .any(|r| (r.offset as u32) < region.end() && (region.offset as u32) < r.end());
+        if overlaps {
+            return Err(MapError::Overlap);
+        }
+
+        let slot = self
+            .regions
+            .iter_mut()
+            .find(|r| r.is_none())
+            .ok_or(MapError::Full)?;
+        *slot = Some(region);
+
+        Ok(self)
+    }
+
+    /// Returns the region containing `offset`
+    fn region(&self, offset: u32) -> Result<&Region<'a>, MemoryError> {
+        self.regions
+            .iter()
+            .flatten()
+            .find(|r| r.contains(offset))
+            .ok_or(MemoryError::OutOfRange)
+    }
+
+    /// Returns the region containing `offset` for modification
+    fn region_mut(&mut self, offset: u32) -> Result<&mut Region<'a>, MemoryError> {
+        self.regions
+            .iter_mut()
+            .flatten()
+            .find(|r| r.contains(offset))
+            .ok_or(MemoryError::OutOfRange)
+    }
+
+    /// Checks that the access of `len` bytes at `offset`
+    /// is completely covered by regions that allow it
+    fn check(&self, offset: u16, len: usize, write: bool) -> Result<(), MemoryError> {
+        let end = offset as u32 + len as u32;
+        let mut pos = offset as u32;
+
+        while pos < end {
+            let region = self.region(pos)?;
+
+            if !region.access.allows(write) {
+                return Err(MemoryError::AccessDenied);
+            }
+
+            pos = region.end();
+        }
+
+        Ok(())
+    }
+}
+
+impl<const N: usize> SlaveMemory for MemoryMap<'_, N> {
+    fn read(&mut self, offset: u16, data: &mut [u8]) -> Result<(), MemoryError> {
+        self.check(offset, data.len(), false)?;
+
+        let mut done = 0;
+        while done < data.len() {
+            let pos = offset as u32 + done as u32;
+            let region = self.region_mut(pos)?;
+
+            let start = (pos - region.offset as u32) as usize;
+            let len = (region.end() - pos).min((data.len() - done) as u32) as usize;
+            let dst = &mut data[done..done + len];
+
+            match &mut region.backing {
+                Backing::Buffer(buf) => dst.copy_from_slice(&buf[start..start + len]),
+                Backing::Handler { handler, .. } => handler(CallbackAction::ReadMemory {
+                    offset: start as u16,
+                    data: dst,
+                })
+                .map_err(|_| MemoryError::Failed)?,
+            }
+
+            done += len;
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u16, data: &[u8]) -> Result<(), MemoryError> {
+        self.check(offset, data.len(), true)?;
+
+        let mut done = 0;
+        while done < data.len() {
+            let pos = offset as u32 + done as u32;
+            let region = self.region_mut(pos)?;
+
+            let start = (pos - region.offset as u32) as usize;
+            let len = (region.end() - pos).min((data.len() - done) as u32) as usize;
+            let src = &data[done..done + len];
+
+            match &mut region.backing {
+                Backing::Buffer(buf) => buf[start..start + len].copy_from_slice(src),
+                Backing::Handler { handler, .. } => handler(CallbackAction::WriteMemory {
+                    offset: start as u16,
+                    data: src,
+                })
+                .map_err(|_| MemoryError::Failed)?,
+            }
+
+            done += len;
+        }
+
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<(), MemoryError> {
+        for region in self.regions.iter_mut().flatten() {
+            if let Backing::Handler { handler, .. } = &mut region.backing {
+                handler(CallbackAction::Reset).map_err(|_| MemoryError::Failed)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use std::cell::RefCell;
+
+use crate::{
+    frame::{Frame, SlaveAddress},
+    slave::{
+        memory_map::{Access, MapError, MemoryMap},
+        transceiver::{CallbackAction, MemoryError, SlaveMemory, Transceiver},
+    },
+    PROTOCOL_VERSION_1,
+};
+
+thread_local! {
+    static ACTIONS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records all actions and answers reads with the relative offset of each byte
+fn recording_handler(action: CallbackAction) -> Result<(), ()> {
+    let msg = match action {
+        CallbackAction::WriteMemory { offset, data } => format!("write {offset} {data:?}"),
+        CallbackAction::ReadMemory { offset, data } => {
+            for (i, d) in data.iter_mut().enumerate() {
+                *d = offset as u8 + i as u8;
+            }
+            format!("read {offset} {}", data.len())
+        }
+        CallbackAction::Reset => "reset".to_string(),
+        CallbackAction::Event(event) => format!("event {event:?}"),
+    };
+    ACTIONS.with_borrow_mut(|a| a.push(msg));
+    Ok(())
+}
+
+fn take_actions() -> Vec<String> {
+    ACTIONS.with_borrow_mut(std::mem::take)
+}
+
+#[test]
+fn build() {
+    let mut a = [0u8; 4];
+    let mut b = [0u8; 4];
+    let mut c = [0u8; 4];
+    let mut d = [0u8; 4];
+
+    let mut map = MemoryMap::<2>::new();
+    map.buffer(0x10, Access::ReadWrite, &mut a).unwrap();
+    assert_eq!(
+        map.buffer(0x13, Access::ReadWrite, &mut d).err(),
+        Some(MapError::Overlap)
+    );
+    assert_eq!(
+        map.handler(0xFFFE, 4, Access::ReadOnly, recording_handler)
+            .err(),
+        Some(MapError::OutOfRange)
+    );
+    map.buffer(0x14, Access::ReadWrite, &mut b).unwrap();
+    assert_eq!(
+        map.buffer(0x20, Access::ReadWrite, &mut c).err(),
+        Some(MapError::Full)
+    );
+}
+
+#[test]
+fn spanning_access() {
+    let mut a = [0u8; 4];
+    let mut b = [0u8; 4];
+
+    let mut map = MemoryMap::<3>::new();
+    map.buffer(0x10, Access::ReadWrite, &mut a)
+        .unwrap()
+        .handler(0x14, 4, Access::ReadWrite, recording_handler)
+        .unwrap()
+        .buffer(0x18, Access::ReadWrite, &mut b)
+        .unwrap();
+    take_actions();
+
+    map.write(0x12, &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+    assert_eq!(take_actions(), vec!["write 0 [3, 4, 5, 6]"]);
+
+    let mut data = [0u8; 8];
+    map.read(0x12, &mut data).unwrap();
+    assert_eq!(data, [1, 2, 0, 1, 2, 3, 7, 8]);
+    assert_eq!(take_actions(), vec!["read 0 4"]);
+
+    map.reset().unwrap();
+    assert_eq!(take_actions(), vec!["reset"]);
+
+    assert_eq!(a, [0, 0, 1, 2]);
+    assert_eq!(b, [7, 8, 0, 0]);
+}
+
+#[test]
+fn illegal_access() {
+    let mut ro = [0u8; 4];
+    let mut wo = [0u8; 4];
+
+    let mut map = MemoryMap::<3>::new();
+    map.buffer(0x00, Access::ReadOnly, &mut ro)
+        .unwrap()
+        .buffer(0x04, Access::WriteOnly, &mut wo)
+        .unwrap()
+        .handler(0x10, 4, Access::ReadWrite, recording_handler)
+        .unwrap();
+    take_actions();
+
+    let mut data = [0u8; 4];
+    assert_eq!(
+        map.write(0x02, &[1, 2, 3, 4]),
+        Err(MemoryError::AccessDenied)
+    );
+    assert_eq!(map.read(0x02, &mut data), Err(MemoryError::AccessDenied));
+
+    // The gap between the regions is not mapped, the
+    // handler must not be called for the valid part
+    assert_eq!(map.write(0x06, &[0u8; 12]), Err(MemoryError::OutOfRange));
+    assert_eq!(map.read(0x12, &mut data), Err(MemoryError::OutOfRange));
+    assert!(take_actions().is_empty());
+
+    assert_eq!(ro, [0u8; 4]);
+    assert_eq!(wo, [0u8; 4]);
+}
+
+#[test]
+fn transceiver() {
+    fn run(t: &mut Transceiver<MemoryMap<1>>, sequence: u8, frame: Frame) {
+        let mut data = vec![0u8; frame.encoded_len().unwrap()];
+        frame.encode(sequence, &mut data).unwrap();
+        for b in data {
+            assert!(t.handle(Some(b)).is_none());
+        }
+    }
+
+    let mut regs = [0u8; 4];
+    let mut map = MemoryMap::<1>::new();
+    map.buffer(0x40, Access::ReadWrite, &mut regs).unwrap();
+
+    let mut scratchpad = [0u8; 0xf];
+    let mut t = Transceiver::with_memory(&mut scratchpad, [1u8; 6], map);
+    run(
+        &mut t,
+        0,
+        Frame::Sync {
+            version: PROTOCOL_VERSION_1,
+        },
+    );
+    run(
+        &mut t,
+        1,
+        Frame::MemWrite {
+            address: SlaveAddress::Physical([1u8; 6]),
+            offset: 0x41,
+            payload: &[0xAA, 0xBB],
+        },
+    );
+    assert!(t.in_sync());
+
+    // Writes outside of the map are rejected by dropping sync
+    run(
+        &mut t,
+        2,
+        Frame::MemWrite {
+            address: SlaveAddress::Physical([1u8; 6]),
+            offset: 0x43,
+            payload: &[0xCC, 0xDD],
+        },
+    );
+    assert!(!t.in_sync());
+
+    assert_eq!(regs, [0, 0xAA, 0xBB, 0]);
+}
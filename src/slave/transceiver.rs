@@ -1,20 +1,25 @@
 //! The transceiver implements the lowest layer of the sondbus communication protocol
 //! and handles synchronization of the communication and memory access.
 
-mod command;
+pub(crate) mod command;
+mod mmu;
 mod state;
+mod system;
 
 #[cfg(test)]
 mod test;
 
+use core::{ops::Range, time::Duration};
+
 use crate::{
     crc8::{CRC8Autosar, CRC},
-    slave::transceiver::state::State,
-    test_log,
+    slave::transceiver::{state::State, system::System},
+    system::{Diagnostics, MmuEntry, SlaveCapabilities, SYSTEM_REGION_START},
+    test_log, START_BYTE,
 };
-use command::Command;
+use command::{AddressingMode, Command};
 
-type StateFunction = fn(&mut Transceiver, rx: Option<u8>) -> Option<u8>;
+type StateFunction<M> = fn(&mut Transceiver<M>, rx: Option<u8>) -> Option<u8>;
 
 /// Consequences of commands that are executed if a
 /// command is finished with the right CRC
@@ -28,6 +33,74 @@ enum Consequence {
     /// Write the contents of the scratchpad to the
     /// slave's memory area
     WriteScratchpad,
+
+    /// Reset the configuration of the slave to its boot state
+    Reset,
+
+    /// Latch the received logical address
+    AssignLogicalAddress,
+
+    /// Report the read that has been served to the application
+    ReadServed,
+
+    /// Respond to a search matching our physical address
+    SearchResponse,
+
+    /// Wait for the slots of a status read, responding in ours
+    StatusResponse,
+}
+
+/// The reasons for the transceiver to loose sync with the bus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncLossReason {
+    /// The CRC of a frame header did not match
+    HeaderCrc,
+
+    /// The CRC of a frame did not match
+    FrameCrc,
+
+    /// The sequence number of a command did not match the expected one
+    Sequence,
+
+    /// An unknown command has been received
+    UnknownCommand,
+
+    /// The master requested a protocol version we do not support
+    VersionMismatch,
+
+    /// The memory rejected an operation
+    CallbackError,
+
+    /// An operation targeting us did not fit into the scratchpad
+    ScratchpadOverflow,
+
+    /// No valid frame has been received within the watchdog timeout
+    Watchdog,
+
+    /// A byte has been received while we expected none or
+    /// a received byte did not match the expected one
+    UnexpectedRx,
+}
+
+/// The events the transceiver reports to the application
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// The transceiver gained sync with the bus
+    SyncGained,
+
+    /// The transceiver lost sync with the bus
+    SyncLost(SyncLossReason),
+
+    /// A write of `len` bytes at `offset` has been committed to memory
+    WriteCommitted { offset: u16, len: u16 },
+
+    /// A read of `len` bytes at `offset` has been sent to the master
+    ReadServed { offset: u16, len: u16 },
+
+    /// No valid frame has been received within the watchdog timeout.
+    /// The application should put its outputs into a safe state.
+    /// This is followed by a [SyncLost](Self::SyncLost) event.
+    WatchdogExpired,
 }
 
 /// The possible actions that can be requested
@@ -38,21 +111,112 @@ pub enum CallbackAction<'a> {
 
     ///  Read from memory memory at `offset` to `data`
     ReadMemory { offset: u16, data: &'a mut [u8] },
+
+    /// Return the memory area to its boot state
+    Reset,
+
+    /// An [Event] occurred, the return value is ignored
+    Event(Event),
 }
 
 /// A type alias for the callback
 pub type Callback = for<'a> fn(CallbackAction<'a>) -> Result<(), ()>;
 
+/// The errors the memory of a slave can report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryError {
+    /// The accessed range is not part of the memory
+    OutOfRange,
+
+    /// The access is not allowed, e.g. a write to read-only memory
+    AccessDenied,
+
+    /// The memory failed to perform the operation
+    Failed,
+}
+
+/// The memory area of a slave that is accessed by the [Transceiver].
+///
+/// In contrast to a [Callback], implementors can carry their own
+/// state. Zero-sized implementors cost nothing at runtime.
+pub trait SlaveMemory {
+    /// Reads from memory at `offset` into `data`
+    fn read(&mut self, offset: u16, data: &mut [u8]) -> Result<(), MemoryError>;
+
+    /// Writes the contents of `data` to memory at `offset`
+    fn write(&mut self, offset: u16, data: &[u8]) -> Result<(), MemoryError>;
+
+    /// Returns the memory area to its boot state
+    fn reset(&mut self) -> Result<(), MemoryError> {
+        Ok(())
+    }
+
+    /// Called whenever an [Event] occurs in the transceiver.
+    ///
+    /// This is called from within [Transceiver::handle], so
+    /// implementors should return quickly.
+    fn event(&mut self, event: Event) {
+        let _ = event;
+    }
+}
+
+impl SlaveMemory for Callback {
+    fn read(&mut self, offset: u16, data: &mut [u8]) -> Result<(), MemoryError> {
+        self(CallbackAction::ReadMemory { offset, data }).map_err(|_| MemoryError::Failed)
+    }
+
+    fn write(&mut self, offset: u16, data: &[u8]) -> Result<(), MemoryError> {
+        self(CallbackAction::WriteMemory { offset, data }).map_err(|_| MemoryError::Failed)
+    }
+
+    fn reset(&mut self) -> Result<(), MemoryError> {
+        self(CallbackAction::Reset).map_err(|_| MemoryError::Failed)
+    }
+
+    fn event(&mut self, event: Event) {
+        let _ = self(CallbackAction::Event(event));
+    }
+}
+
+/// The errors that can occur while handling a complete frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// The response buffer is smaller than
+    /// [max_response_len()](Transceiver::max_response_len)
+    ResponseBufferTooSmall,
+}
+
+/// The timeouts after which the transceiver aborts an incomplete frame.
+///
+/// Timeouts are disabled if they are `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timeouts {
+    /// The maximum time between two bytes of a frame
+    pub inter_byte: Option<Duration>,
+
+    /// The maximum time a complete frame may take
+    pub frame: Option<Duration>,
+
+    /// The time after which an empty ack slot of the working counter is
+    /// skipped on a shared medium, as missing slaves leave their slots empty.
+    /// This has to be shorter than `inter_byte` and the byte timeout of
+    /// the master, so the slaves after a gap acknowledge in time.
+    pub ack_slot: Option<Duration>,
+}
+
 /// Represents a transceiver in the sondbus model.
 ///
 /// The transceiver implements the lowest layer of the sondbus communication protocol
 /// and handles synchronization of the communication and slave memory access.
-pub struct Transceiver<'a> {
+pub struct Transceiver<'a, M: SlaveMemory = Callback> {
     state: State,
     crc: CRC8Autosar,
     cur_cmd: Command,
 
     in_sync: bool,
+    /// Set while a frame on a packet-oriented link is processed as if we
+    /// were in sync, which is only confirmed once a CRC has been validated
+    sync_pending: bool,
 
     /// The activity flag gets set to true if a valid frame
     /// or frame header has been received, indicating valid
@@ -60,24 +224,69 @@ pub struct Transceiver<'a> {
     /// consumer of the transceiver to detect timeouts.
     activity_flag: bool,
 
+    /// Set if a byte has been received or sent
+    /// since the last [tick](Self::tick)
+    byte_seen: bool,
+    last_byte: Duration,
+    frame_start: Option<Duration>,
+    timeouts: Timeouts,
+
+    /// The CRCs over the bytes received and passed on in forwarding mode
+    forward_rx_crc: CRC8Autosar,
+    forward_tx_crc: CRC8Autosar,
+    /// The byte to put into the next slot in forwarding mode
+    forward_tx: Option<u8>,
+    /// Whether we are operated in forwarding mode
+    forwarding: bool,
+    /// Whether we are operated on a packet-oriented link
+    framed: bool,
+
+    /// Set along with the activity flag, but reserved for the watchdog
+    watchdog_fed: bool,
+    watchdog_last: Duration,
+
     sequence_no: u8,
 
     pos: u16,
 
     mem_cmd_addr: [u8; 6],
+    /// The position of a position addressed memory command, if any
+    mem_cmd_position: Option<u16>,
+    /// The amount of bits of the prefix of a search
+    search_len: u8,
+
+    /// Whether we take part in status reads, which requires ticks
+    status_read: bool,
+    /// Our slot in the current status read, if any, the amount
+    /// of slots and the time every slot takes in microseconds
+    status_slot: Option<u8>,
+    status_slots: u8,
+    slot_time: u16,
+    /// The time the slots of the current status read started
+    slots_start: Option<Duration>,
+
+    /// The amount of ack slots following the current memory command
+    /// and our slot, latched from the system region at its start
+    ack_slots: u8,
+    ack_slot: u8,
+    /// Whether we applied the current memory command
+    acked: bool,
     mem_cmd_offset: u16,
     mem_cmd_size: u16,
 
     physical_address: [u8; 6],
     logical_address: [u8; 2],
+    new_logical_address: [u8; 2],
 
     scratchpad: &'a mut [u8],
     consequence: Consequence,
 
-    callback: Callback,
+    system: System,
+
+    memory: M,
 }
 
-impl<'a> Transceiver<'a> {
+impl<'a> Transceiver<'a, Callback> {
     /// Creates a new transceiver
     /// # Arguments
     /// * `scratchpad` - The scratchpad memory to operate on
@@ -87,6 +296,21 @@ impl<'a> Transceiver<'a> {
         scratchpad: &'a mut [u8],
         physical_address: [u8; 6],
         callback: Callback,
+    ) -> Self {
+        Self::with_memory(scratchpad, physical_address, callback)
+    }
+}
+
+impl<'a, M: SlaveMemory> Transceiver<'a, M> {
+    /// Creates a new transceiver operating on a [SlaveMemory]
+    /// # Arguments
+    /// * `scratchpad` - The scratchpad memory to operate on
+    /// * `physical_address` - The unique physical address of the transceiver
+    /// * `memory` - The memory area of the slave
+    pub const fn with_memory(
+        scratchpad: &'a mut [u8],
+        physical_address: [u8; 6],
+        memory: M,
     ) -> Self {
         Self {
             state: State::WaitForStart,
@@ -94,21 +318,50 @@ impl<'a> Transceiver<'a> {
             cur_cmd: Command::new(0),
 
             in_sync: false,
+            sync_pending: false,
             activity_flag: false,
+            byte_seen: false,
+            last_byte: Duration::ZERO,
+            frame_start: None,
+            timeouts: Timeouts {
+                inter_byte: None,
+                frame: None,
+                ack_slot: None,
+            },
+            forward_rx_crc: CRC8Autosar::new_const(),
+            forward_tx_crc: CRC8Autosar::new_const(),
+            forward_tx: None,
+            forwarding: false,
+            framed: false,
+            watchdog_fed: false,
+            watchdog_last: Duration::ZERO,
             sequence_no: 0,
 
             pos: 0,
             mem_cmd_addr: [0u8; 6],
+            mem_cmd_position: None,
+            search_len: 0,
+            status_read: false,
+            status_slot: None,
+            status_slots: 0,
+            slot_time: 0,
+            slots_start: None,
+            ack_slots: 0,
+            ack_slot: 0,
+            acked: false,
             mem_cmd_offset: 0,
             mem_cmd_size: 0,
 
             physical_address,
             logical_address: [0u8; 2],
+            new_logical_address: [0u8; 2],
 
+            system: System::new(scratchpad.len()),
             scratchpad,
 
             consequence: Consequence::None,
-            callback,
+
+            memory,
         }
     }
 
@@ -120,9 +373,94 @@ impl<'a> Transceiver<'a> {
     /// Sets the internal `in_sync` flag false, effectively
     /// taking the transceiver offline until the next `Sync`
     /// command comes around from the master
-    pub fn loose_sync(&mut self) {
-        test_log!("Lost sync!");
-        self.in_sync = false;
+    /// # Arguments
+    /// * `reason` - The reason for loosing sync, reported in the [Event::SyncLost]
+    pub fn loose_sync(&mut self, reason: SyncLossReason) {
+        test_log!("Lost sync: {:?}", reason);
+
+        let diagnostics = &mut self.system.diagnostics;
+        match reason {
+            SyncLossReason::HeaderCrc => system::count(&mut diagnostics.header_crc_errors),
+            SyncLossReason::FrameCrc => system::count(&mut diagnostics.frame_crc_errors),
+            SyncLossReason::Sequence => system::count(&mut diagnostics.sequence_errors),
+            SyncLossReason::UnknownCommand => system::count(&mut diagnostics.unknown_commands),
+            SyncLossReason::CallbackError | SyncLossReason::ScratchpadOverflow => {
+                system::count(&mut diagnostics.memory_errors)
+            }
+            SyncLossReason::VersionMismatch
+            | SyncLossReason::UnexpectedRx
+            | SyncLossReason::Watchdog => {}
+        }
+
+        // The sync taken over from a frame that turned out to be
+        // corrupted has never been gained, so it is not lost either
+        if core::mem::take(&mut self.sync_pending) {
+            self.in_sync = false;
+        } else if self.in_sync {
+            self.in_sync = false;
+            system::count(&mut self.system.diagnostics.sync_lost);
+            self.memory.event(Event::SyncLost(reason));
+        }
+    }
+
+    /// Returns the memory area of the slave
+    pub fn memory(&mut self) -> &mut M {
+        &mut self.memory
+    }
+
+    /// Returns the unique physical address of the transceiver
+    pub fn physical_address(&self) -> [u8; 6] {
+        self.physical_address
+    }
+
+    /// Returns the logical address of the transceiver
+    pub fn logical_address(&self) -> [u8; 2] {
+        self.logical_address
+    }
+
+    /// Sets the logical address of the transceiver.
+    ///
+    /// The logical address is usually assigned by the master,
+    /// but the application can preset it to skip this step.
+    /// # Arguments
+    /// * `logical_address` - The new logical address
+    pub fn set_logical_address(&mut self, logical_address: [u8; 2]) {
+        self.logical_address = logical_address;
+    }
+
+    /// Returns the capabilities of the transceiver, as they
+    /// are served to the master in the capability descriptor
+    pub fn capabilities(&self) -> SlaveCapabilities {
+        self.system.capabilities()
+    }
+
+    /// Returns the diagnostics counters of the transceiver, as
+    /// they are served to the master in the diagnostics block
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.system.diagnostics
+    }
+
+    /// Resets all diagnostics counters to `0`
+    pub fn clear_diagnostics(&mut self) {
+        self.system.diagnostics = Diagnostics::default();
+    }
+
+    /// Returns the entry of the MMU at `index`, if it is enabled
+    pub fn mmu_entry(&self, index: usize) -> Option<MmuEntry> {
+        self.system.mmu.entry(index)
+    }
+
+    /// Configures the entry of the MMU at `index`.
+    ///
+    /// The MMU is usually configured by the master through the
+    /// system region, but the application can preset it.
+    /// # Arguments
+    /// * `index` - The index of the entry to configure
+    /// * `entry` - The new entry or `None` to disable it
+    /// # Returns
+    /// `false` if there is no entry at `index`
+    pub fn set_mmu_entry(&mut self, index: usize, entry: Option<MmuEntry>) -> bool {
+        self.system.mmu.set_entry(index, entry)
     }
 
     /// Returns the current state of the activity flag,
@@ -155,9 +493,269 @@ impl<'a> Transceiver<'a> {
 
         test_log!("Transitioned from {:?} to {:?}", old_state, self.state);
 
+        if rx.is_some() || res.is_some() {
+            self.byte_seen = true;
+        }
+        if self.state == State::WaitForStart {
+            self.frame_start = None;
+        }
+
+        res
+    }
+
+    /// Processes a byte on the fly in a peer-to-peer chain of slaves, where
+    /// every byte received from upstream is passed on downstream.
+    ///
+    /// Bytes the transceiver would transmit on a shared medium replace
+    /// the placeholders the master sends in their place and the position
+    /// of position addressed commands is decremented. The data of broadcast
+    /// reads, which only slaves in forwarding mode answer, is combined with
+    /// the data of the slaves upstream by a bitwise OR. As this changes the
+    /// frame, the CRCs are recomputed over the forwarded bytes. A corrupted
+    /// frame keeps its corrupted CRCs, so the error is not hidden from the
+    /// slaves further downstream and the master.
+    /// # Arguments
+    /// * `rx` - The byte received from upstream
+    /// # Returns
+    /// The byte to pass downstream
+    pub fn forward(&mut self, rx: u8) -> u8 {
+        self.forwarding = true;
+
+        if self.state == State::WaitForStart && self.forward_tx.is_none() {
+            self.forward_rx_crc = CRC8Autosar::new();
+            self.forward_tx_crc = CRC8Autosar::new();
+        }
+        // We count in our ack slot instead of transmitting our ack
+        if self.state == State::MEMAckSlots && self.forward_tx.is_none() {
+            let tx = if self.pos == self.ack_slot as u16 {
+                rx.wrapping_add(self.acked as u8)
+            } else {
+                rx
+            };
+            self.next_ack_slot();
+            return tx;
+        }
+
+        let crc_slot = matches!(self.state, State::MEMHeaderCRC | State::WaitForCRC);
+        let search_slot = self.state == State::SearchResponse;
+        let position_low = (self.state == State::MEMPosition).then_some(self.pos == 0);
+
+        // Check whether we would transmit in this slot
+        if self.forward_tx.is_none() {
+            self.forward_tx = self.handle(None);
+        }
+
+        // The difference of a received CRC to the one over the received bytes
+        let mismatch = rx ^ self.forward_rx_crc.finalize();
+
+        let tx = match self.forward_tx.take() {
+            Some(tx) if search_slot => tx,
+            // Only the final CRC is sent at the end of the frame
+            Some(_) if matches!(self.state, State::WaitForStart | State::MEMAckSlots) => {
+                if mismatch != 0 {
+                    self.loose_sync(SyncLossReason::FrameCrc);
+                }
+                self.forward_tx_crc.finalize() ^ mismatch
+            }
+            Some(tx) if self.is_broadcast_read() => tx | rx,
+            Some(tx) => tx,
+            None if crc_slot => {
+                self.forward_tx = self.handle(Some(self.crc.finalize() ^ mismatch));
+                self.forward_tx_crc.finalize() ^ mismatch
+            }
+            None => {
+                self.forward_tx = self.handle(Some(rx));
+
+                // The position is little-endian, so we know
+                // the borrow of the low byte for the high one
+                match position_low {
+                    Some(true) => rx.wrapping_sub(1),
+                    Some(false) => (self.mem_cmd_position.unwrap_or(0).wrapping_sub(1) >> 8) as u8,
+                    None => rx,
+                }
+            }
+        };
+
+        self.forward_rx_crc.update_single(rx);
+        self.forward_tx_crc.update_single(tx);
+        tx
+    }
+
+    /// Processes a complete frame received from a packet-oriented link.
+    ///
+    /// As the link preserves the frame boundaries, no `Sync` command is
+    /// needed to recover the byte alignment: A transceiver that is out of
+    /// sync takes over the sequence number of the frame and processes it,
+    /// gaining sync once the CRC of the frame or its header has validated.
+    /// Frames that end early or continue with data of other slaves are
+    /// dropped at the end of the frame. Logical memory reads can only be
+    /// answered if the slave maps the complete logical range itself.
+    /// As the responses of other slaves do not pass through the link,
+    /// the ack slots of the working counter are all transmitted, leaving
+    /// the slots of the other slaves empty.
+    /// # Arguments
+    /// * `frame` - The complete frame, starting with the start byte
+    /// * `response` - The buffer for the response, which needs to hold at least
+    ///   [max_response_len()](Self::max_response_len) bytes. Smaller buffers
+    ///   are rejected before the frame is processed.
+    /// # Returns
+    /// The length of the response in `response`, `0` if there is none
+    pub fn handle_frame(&mut self, frame: &[u8], response: &mut [u8]) -> Result<usize, FrameError> {
+        if response.len() < self.max_response_len() {
+            return Err(FrameError::ResponseBufferTooSmall);
+        }
+
+        self.framed = true;
+        self.state = State::WaitForStart;
+
+        if let (false, [START_BYTE, command, ..]) = (self.in_sync, frame) {
+            self.in_sync = true;
+            self.sync_pending = true;
+            self.sequence_no = ((command >> 6) + 0b11) & 0b11;
+        }
+
+        let mut len = 0;
+        let mut rx = frame.iter();
+        let res = loop {
+            // Once the frame has been consumed, we
+            // transmit until we have nothing left to say
+            let tx = match rx.next() {
+                Some(b) => self.handle(Some(*b)),
+                None => match self.handle(None) {
+                    Some(tx) => Some(tx),
+                    None => break Ok(len),
+                },
+            };
+
+            if let Some(tx) = tx {
+                let Some(slot) = response.get_mut(len) else {
+                    break Err(FrameError::ResponseBufferTooSmall);
+                };
+                *slot = tx;
+                len += 1;
+            }
+        };
+
+        // Frames that end before any CRC could be validated do not count
+        if core::mem::take(&mut self.sync_pending) {
+            self.in_sync = false;
+        }
+
+        self.state = State::WaitForStart;
         res
     }
 
+    /// Returns the size of the buffer [handle_frame()](Self::handle_frame) needs
+    /// to hold any response: A read of the whole scratchpad, its CRC and the
+    /// largest number of ack slots the working counter can be configured to.
+    pub fn max_response_len(&self) -> usize {
+        self.scratchpad.len() + 1 + u8::MAX as usize
+    }
+
+    /// Enables responding to status reads, which are disabled by default.
+    ///
+    /// The slots of a status read are timed by the [ticks](Self::tick),
+    /// so this must only be enabled if the transceiver is ticked
+    /// periodically. Otherwise, the status reads are ignored like
+    /// any other frame that is not targeted at us.
+    /// # Arguments
+    /// * `enabled` - Whether to respond to status reads
+    pub fn set_status_read(&mut self, enabled: bool) {
+        self.status_read = enabled;
+    }
+
+    /// Sets the timeouts after which incomplete frames are aborted
+    /// # Arguments
+    /// * `timeouts` - The new timeouts, disabled by default
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = timeouts;
+    }
+
+    /// Returns the timeout of the watchdog, if it is enabled
+    pub fn watchdog(&self) -> Option<Duration> {
+        self.system.watchdog_timeout()
+    }
+
+    /// Configures the watchdog, which drops sync and emits an
+    /// [Event::WatchdogExpired] if no valid frame has been received
+    /// within the `timeout` while in sync.
+    ///
+    /// The watchdog is usually configured by the master through the
+    /// system region, but the application can preset it. It is disabled
+    /// by the [Reset](crate::CMD_RESET) command.
+    /// # Arguments
+    /// * `timeout` - The timeout, limited to `u16::MAX` milliseconds, or `None` to disable it
+    pub fn set_watchdog(&mut self, timeout: Option<Duration>) {
+        self.system.watchdog = match timeout {
+            Some(timeout) => timeout.as_millis().clamp(1, u16::MAX as u128) as u16,
+            None => 0,
+        };
+    }
+
+    /// Advances the time of the transceiver, aborting the current frame
+    /// if it exceeds one of the configured [Timeouts], skipping empty ack
+    /// slots and checking the [watchdog](Self::set_watchdog).
+    ///
+    /// This should be called periodically by the application. Bytes are
+    /// timestamped with the `now` of the next tick, so the timeouts are
+    /// only as precise as the interval between the ticks. In contrast to
+    /// the [activity flag](Self::get_activity_flag), which detects a silent
+    /// bus, this resynchronizes the transceiver after a truncated frame.
+    /// # Arguments
+    /// * `now` - The current time, measured from an arbitrary but fixed point
+    /// # Returns
+    /// Whether an incomplete frame has been aborted
+    pub fn tick(&mut self, now: Duration) -> bool {
+        self.check_watchdog(now);
+
+        // The slots of a status read are silent unless a slave responds
+        if let State::StatusSlots | State::StatusResponse = self.state {
+            self.byte_seen = false;
+            self.tick_slots(now);
+            return false;
+        }
+
+        if self.state == State::WaitForStart {
+            self.byte_seen = false;
+            return false;
+        }
+
+        if core::mem::take(&mut self.byte_seen) {
+            self.last_byte = now;
+        }
+        let frame_start = *self.frame_start.get_or_insert(now);
+
+        let expired = |timeout: Option<Duration>, since: Duration| {
+            timeout.is_some_and(|timeout| now.saturating_sub(since) > timeout)
+        };
+
+        // On a shared medium, nobody fills the slots of missing slaves
+        if self.state == State::MEMAckSlots
+            && !self.forwarding
+            && !self.framed
+            && self.pos != self.ack_slot as u16
+            && expired(self.timeouts.ack_slot, self.last_byte)
+        {
+            self.last_byte = now;
+            self.next_ack_slot();
+            if self.state == State::WaitForStart {
+                self.frame_start = None;
+            }
+            return false;
+        }
+
+        if expired(self.timeouts.inter_byte, self.last_byte)
+            || expired(self.timeouts.frame, frame_start)
+        {
+            test_log!("Frame timed out in {:?}", self.state);
+            self.state = State::WaitForStart;
+            self.frame_start = None;
+            return true;
+        }
+
+        false
+    }
+
     /// Returns whether the data that is coming up to this point
     /// is targeted to us or another slave
     fn is_targeted(&self) -> bool {
@@ -165,12 +763,246 @@ impl<'a> Transceiver<'a> {
             return false;
         }
 
-        match self.cur_cmd.mem_slave_address_len() {
-            0 => true,
-            2 => self.mem_cmd_addr[0..2] == self.logical_address,
-            6 => self.mem_cmd_addr == self.physical_address,
-            _ => false,
+        // On a shared medium, all slaves receive the same position
+        if let Some(position) = self.mem_cmd_position {
+            return position == 0 && self.forwarding;
         }
+
+        match self.cur_cmd.mem_slave_addressing_mode() {
+            // On a shared medium, the responses to broadcast reads would collide
+            AddressingMode::Broadcast => self.cur_cmd.mem_is_write_cmd() || self.forwarding,
+            AddressingMode::Logical => self.mem_cmd_addr[0..2] == self.logical_address,
+            AddressingMode::Physical => self.mem_cmd_addr == self.physical_address,
+            AddressingMode::None => self.is_logical_memory_mapped(),
+        }
+    }
+
+    /// Returns whether the current command is a broadcast memory read
+    fn is_broadcast_read(&self) -> bool {
+        self.cur_cmd.is_mem_cmd()
+            && self.cur_cmd.mem_is_read_cmd()
+            && self.mem_cmd_position.is_none()
+            && matches!(
+                self.cur_cmd.mem_slave_addressing_mode(),
+                AddressingMode::Broadcast
+            )
+    }
+
+    /// Returns whether the current command is a logical memory operation
+    fn is_logical_memory_cmd(&self) -> bool {
+        matches!(
+            self.cur_cmd.mem_slave_addressing_mode(),
+            AddressingMode::None
+        )
+    }
+
+    /// Returns whether the MMU maps any part of the current
+    /// logical memory operation to our memory area
+    fn is_logical_memory_mapped(&self) -> bool {
+        self.system
+            .mmu
+            .windows(
+                self.mem_cmd_offset,
+                self.mem_cmd_size,
+                self.cur_cmd.mem_is_write_cmd(),
+            )
+            .next()
+            .is_some()
+    }
+
+    /// Returns whether the current command needs more
+    /// scratchpad memory than we have available.
+    ///
+    /// Such commands can be tolerated on the bus, but we
+    /// cannot be targeted by them (partial support).
+    fn exceeds_scratchpad(&self) -> bool {
+        let len = if self.is_logical_memory_cmd() {
+            self.system.mmu.mapped_len(
+                self.mem_cmd_offset,
+                self.mem_cmd_size,
+                self.cur_cmd.mem_is_write_cmd(),
+            )
+        } else {
+            self.mem_cmd_size as usize
+        };
+
+        len > self.scratchpad.len()
+    }
+
+    /// Sets the `in_sync` flag, notifying the memory if it changed
+    fn gain_sync(&mut self) {
+        if !self.in_sync {
+            self.in_sync = true;
+            system::count(&mut self.system.diagnostics.sync_gained);
+            self.memory.event(Event::SyncGained);
+        }
+    }
+
+    /// Returns whether the prefix of the current search matches our physical address
+    fn search_matches(&self) -> bool {
+        let len = self.search_len.min(48) as usize;
+        let (bytes, bits) = (len / 8, len % 8);
+        let mask = !(0xFFu8 >> bits);
+
+        self.mem_cmd_addr[..bytes] == self.physical_address[..bytes]
+            && (bits == 0 || (self.mem_cmd_addr[bytes] ^ self.physical_address[bytes]) & mask == 0)
+    }
+
+    /// Prepares our response to a status read in the scratchpad, which is
+    /// made up of our logical address, the data and a CRC over both
+    fn prepare_status(&mut self) {
+        let size = self.mem_cmd_size as usize;
+
+        // Slaves that cannot respond leave their slot empty
+        if size + 3 > self.scratchpad.len()
+            || self.read_memory(self.mem_cmd_offset, 2..2 + size).is_err()
+        {
+            self.status_slot = None;
+            return;
+        }
+
+        self.scratchpad[0..2].copy_from_slice(&self.logical_address);
+        self.scratchpad[2 + size] = CRC8Autosar::new()
+            .update_move(&self.scratchpad[..2 + size])
+            .finalize();
+    }
+
+    /// Advances the slots of a status read, starting our response once our
+    /// slot has come and ending the frame once all slots have passed
+    fn tick_slots(&mut self, now: Duration) {
+        let start = *self.slots_start.get_or_insert(now);
+        let elapsed = now.saturating_sub(start);
+        let slot_time = Duration::from_micros(self.slot_time as u64);
+
+        if elapsed >= slot_time * self.status_slots as u32 {
+            self.pos = 0;
+            self.state = State::WaitForStart;
+            self.frame_start = None;
+        } else if let Some(slot) = self.status_slot {
+            if self.state == State::StatusSlots && elapsed >= slot_time * slot as u32 {
+                self.pos = 0;
+                self.state = State::StatusResponse;
+            }
+        }
+    }
+
+    /// Returns the state following the end of a valid frame,
+    /// which are the ack slots of the working counter, if enabled
+    fn frame_end(&mut self) -> State {
+        self.pos = 0;
+
+        if self.ack_slots > 0 && self.in_sync {
+            State::MEMAckSlots
+        } else {
+            State::WaitForStart
+        }
+    }
+
+    /// Advances to the next ack slot, ending the frame after the last one
+    fn next_ack_slot(&mut self) {
+        self.pos += 1;
+
+        if self.pos >= self.ack_slots as u16 {
+            self.pos = 0;
+            self.state = State::WaitForStart;
+        }
+    }
+
+    /// Marks the reception of a valid frame or frame header
+    fn valid_activity(&mut self) {
+        self.activity_flag = true;
+        self.watchdog_fed = true;
+
+        if core::mem::take(&mut self.sync_pending) {
+            self.in_sync = false;
+            self.gain_sync();
+        }
+    }
+
+    /// Drops sync if the watchdog has not been fed within its timeout
+    fn check_watchdog(&mut self, now: Duration) {
+        // The watchdog is only armed while we are in sync
+        if core::mem::take(&mut self.watchdog_fed) || !self.in_sync {
+            self.watchdog_last = now;
+            return;
+        }
+
+        let Some(timeout) = self.system.watchdog_timeout() else {
+            return;
+        };
+
+        if now.saturating_sub(self.watchdog_last) > timeout {
+            test_log!("Watchdog expired");
+            self.memory.event(Event::WatchdogExpired);
+            self.loose_sync(SyncLossReason::Watchdog);
+
+            // A frame in flight must not be committed after the safe state
+            self.state = State::WaitForStart;
+            self.frame_start = None;
+        }
+    }
+
+    /// Reports the event for the current read command to the memory
+    fn read_served(&mut self) {
+        self.acked = true;
+        self.memory.event(Event::ReadServed {
+            offset: self.mem_cmd_offset,
+            len: self.mem_cmd_size,
+        });
+    }
+
+    /// Reads from the memory area at `offset` into the `range` of the scratchpad
+    fn read_memory(&mut self, offset: u16, range: Range<usize>) -> Result<(), MemoryError> {
+        let data = &mut self.scratchpad[range];
+        if offset >= SYSTEM_REGION_START {
+            self.system.read(offset, data)
+        } else if offset as usize + data.len() > SYSTEM_REGION_START as usize {
+            Err(MemoryError::OutOfRange)
+        } else {
+            self.memory.read(offset, data)
+        }
+    }
+
+    /// Writes the `range` of the scratchpad to the memory area at `offset`
+    fn write_memory(&mut self, offset: u16, range: Range<usize>) -> Result<(), MemoryError> {
+        let data = &self.scratchpad[range];
+        if offset >= SYSTEM_REGION_START {
+            self.system.write(offset, data)
+        } else if offset as usize + data.len() > SYSTEM_REGION_START as usize {
+            Err(MemoryError::OutOfRange)
+        } else {
+            self.memory.write(offset, data)
+        }
+    }
+
+    /// Reads the data for the current read command into the scratchpad
+    fn load_scratchpad(&mut self) -> Result<(), MemoryError> {
+        if !self.is_logical_memory_cmd() {
+            return self.read_memory(self.mem_cmd_offset, 0..self.mem_cmd_size as usize);
+        }
+
+        let mmu = self.system.mmu.clone();
+        for w in mmu.windows(self.mem_cmd_offset, self.mem_cmd_size, false) {
+            let start = w.scratchpad as usize;
+            self.read_memory(w.physical, start..start + w.len as usize)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the scratchpad contents of the current write command to memory
+    fn commit_scratchpad(&mut self) -> Result<(), MemoryError> {
+        if !self.is_logical_memory_cmd() {
+            return self.write_memory(self.mem_cmd_offset, 0..self.mem_cmd_size as usize);
+        }
+
+        let mmu = self.system.mmu.clone();
+        for w in mmu.windows(self.mem_cmd_offset, self.mem_cmd_size, true) {
+            let start = w.scratchpad as usize;
+            self.write_memory(w.physical, start..start + w.len as usize)?;
+        }
+
+        Ok(())
     }
 
     fn update_crc(&mut self, v: u8) {
@@ -15,10 +15,36 @@ impl Command {
         Self { value }
     }
 
+    /// Creates a memory command from its individual parts
+    pub const fn new_mem(
+        sequence: u8,
+        write: bool,
+        addressing_mode: u8,
+        long_offset: bool,
+        long_size: bool,
+    ) -> Self {
+        Self::new(
+            (sequence & 0b11) << 6
+                | 1 << 5
+                | (long_size as u8) << 4
+                | (long_offset as u8) << 3
+                | (addressing_mode & 0b11) << 1
+                | write as u8,
+        )
+    }
+
     pub fn raw(&self) -> u8 {
         self.value
     }
 
+    pub fn sequence(&self) -> u8 {
+        self.value >> 6
+    }
+
+    pub fn without_sequence(&self) -> u8 {
+        self.value & 0b11_1111
+    }
+
     pub fn mem_slave_addressing_mode(&self) -> AddressingMode {
         match (self.value & 0b110) >> 1 {
             0b00 => AddressingMode::Broadcast,
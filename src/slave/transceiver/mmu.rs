@@ -0,0 +1,143 @@
+use crate::system::{MmuEntry, MMU_COUNT, MMU_ENTRY_SIZE};
+
+/// The part of a logical memory operation that is
+/// mapped onto the memory area by a single MMU entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+    /// The first logical address of the window
+    pub logical: u32,
+
+    /// The amount of bytes in the window
+    pub len: u16,
+
+    /// The offset in the memory area
+    pub physical: u16,
+
+    /// The position of the window in the scratchpad
+    pub scratchpad: u16,
+}
+
+impl Window {
+    /// Returns the position in the scratchpad for
+    /// the `logical` address, if it is inside the window
+    pub fn scratchpad_pos(&self, logical: u32) -> Option<usize> {
+        if logical >= self.logical && logical < self.logical + self.len as u32 {
+            Some(self.scratchpad as usize + (logical - self.logical) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+/// The memory management unit of a slave, mapping windows
+/// of the logical address space onto the memory area
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mmu {
+    entries: [Option<MmuEntry>; MMU_COUNT],
+}
+
+impl Mmu {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; MMU_COUNT],
+        }
+    }
+
+    pub fn entry(&self, index: usize) -> Option<MmuEntry> {
+        self.entries.get(index).copied().flatten()
+    }
+
+    /// Sets the entry at `index`, returning `false` if there is no such entry
+    pub fn set_entry(&mut self, index: usize, entry: Option<MmuEntry>) -> bool {
+        match self.entries.get_mut(index) {
+            Some(e) => {
+                *e = entry;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Disables all entries
+    pub fn clear(&mut self) {
+        self.entries = [None; MMU_COUNT];
+    }
+
+    /// Returns the table of entries as it is represented in memory
+    pub fn to_bytes(&self) -> [u8; MMU_COUNT * MMU_ENTRY_SIZE] {
+        let mut bytes = [0u8; MMU_COUNT * MMU_ENTRY_SIZE];
+        for (entry, chunk) in self.entries.iter().zip(bytes.chunks_mut(MMU_ENTRY_SIZE)) {
+            chunk.copy_from_slice(&MmuEntry::to_bytes(entry.as_ref()));
+        }
+        bytes
+    }
+
+    /// Loads the table of entries from its representation in memory
+    pub fn load_bytes(&mut self, bytes: &[u8; MMU_COUNT * MMU_ENTRY_SIZE]) {
+        for (entry, chunk) in self.entries.iter_mut().zip(bytes.chunks(MMU_ENTRY_SIZE)) {
+            *entry = MmuEntry::from_bytes(chunk.try_into().unwrap());
+        }
+    }
+
+    /// Returns the windows a logical memory operation maps to.
+    ///
+    /// The windows are packed into the scratchpad one after the other.
+    /// # Arguments
+    /// * `offset` - The logical start address of the operation
+    /// * `size` - The size of the operation
+    /// * `write` - Whether the operation is a write
+    pub fn windows(
+        &self,
+        offset: u16,
+        size: u16,
+        write: bool,
+    ) -> impl Iterator<Item = Window> + '_ {
+        let start = offset as u32;
+        let end = start + size as u32;
+
+        self.entries
+            .iter()
+            .flatten()
+            .filter(move |e| if write { e.write } else { e.read })
+            .filter_map(move |e| {
+                let e_start = e.logical_start as u32;
+                let e_end = e_start + e.length as u32;
+
+                let w_start = start.max(e_start);
+                let w_end = end.min(e_end);
+
+                (w_start < w_end).then(|| Window {
+                    logical: w_start,
+                    len: (w_end - w_start) as u16,
+                    physical: e.physical_start.wrapping_add((w_start - e_start) as u16),
+                    scratchpad: 0,
+                })
+            })
+            .scan(0u16, |pos, mut w| {
+                w.scratchpad = *pos;
+                *pos = pos.saturating_add(w.len);
+                Some(w)
+            })
+    }
+
+    /// Returns the amount of bytes of a logical memory
+    /// operation that are mapped by us
+    pub fn mapped_len(&self, offset: u16, size: u16, write: bool) -> usize {
+        self.windows(offset, size, write)
+            .map(|w| w.len as usize)
+            .sum()
+    }
+
+    /// Returns the position in the scratchpad for the `logical` address
+    /// of an operation, if this address is mapped by us
+    pub fn scratchpad_pos(
+        &self,
+        offset: u16,
+        size: u16,
+        write: bool,
+        logical: u32,
+    ) -> Option<usize> {
+        self.windows(offset, size, write)
+            .find_map(|w| w.scratchpad_pos(logical))
+    }
+}
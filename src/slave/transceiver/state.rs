@@ -1,38 +1,64 @@
-use crate::slave::transceiver::{StateFunction, Transceiver};
+use core::marker::PhantomData;
 
+use crate::slave::transceiver::{SlaveMemory, StateFunction, Transceiver};
+
+mod state_assign_address;
+mod state_mem_ack_slots;
 mod state_mem_address;
 mod state_mem_header_crc;
+mod state_mem_logical_payload;
 mod state_mem_offset;
+mod state_mem_position;
 mod state_mem_rx_payload;
 mod state_mem_size;
 mod state_mem_tx_payload;
+mod state_search;
+mod state_search_response;
 mod state_send_crc;
+mod state_status;
+mod state_status_response;
+mod state_status_slots;
 mod state_sync;
 mod state_wait_for_cmd;
 mod state_wait_for_crc;
 mod state_wait_for_start;
 
-/// Enumerates the state functions that the control flow
-/// jumps to for the individual states.
-///
-/// Make sure that the order is EXACTLY the same as in [State]
-const STATES: [StateFunction; 11] = [
-    state_wait_for_start::state_wait_for_start,
-    state_wait_for_cmd::state_wait_for_cmd,
-    state_sync::state_sync,
-    state_mem_address::state_mem_address,
-    state_mem_offset::state_mem_offset,
-    state_mem_size::state_mem_size,
-    state_mem_rx_payload::state_mem_rx_payload,
-    state_mem_header_crc::state_mem_header_crc,
-    state_mem_tx_payload::state_mem_tx_payload,
-    state_send_crc::state_send_crc,
-    state_wait_for_crc::state_wait_for_crc,
-];
+/// Holds the table of state functions for a [Transceiver]
+/// operating on the memory `M`
+struct States<M>(PhantomData<M>);
+
+impl<M: SlaveMemory> States<M> {
+    /// Enumerates the state functions that the control flow
+    /// jumps to for the individual states.
+    ///
+    /// Make sure that the order is EXACTLY the same as in [State]
+    const TABLE: [StateFunction<M>; 20] = [
+        state_wait_for_start::state_wait_for_start::<M>,
+        state_wait_for_cmd::state_wait_for_cmd::<M>,
+        state_sync::state_sync::<M>,
+        state_mem_address::state_mem_address::<M>,
+        state_mem_offset::state_mem_offset::<M>,
+        state_mem_size::state_mem_size::<M>,
+        state_mem_rx_payload::state_mem_rx_payload::<M>,
+        state_mem_header_crc::state_mem_header_crc::<M>,
+        state_mem_tx_payload::state_mem_tx_payload::<M>,
+        state_send_crc::state_send_crc::<M>,
+        state_wait_for_crc::state_wait_for_crc::<M>,
+        state_assign_address::state_assign_address::<M>,
+        state_mem_logical_payload::state_mem_logical_payload::<M>,
+        state_mem_position::state_mem_position::<M>,
+        state_mem_ack_slots::state_mem_ack_slots::<M>,
+        state_search::state_search::<M>,
+        state_search_response::state_search_response::<M>,
+        state_status::state_status::<M>,
+        state_status_slots::state_status_slots::<M>,
+        state_status_response::state_status_response::<M>,
+    ];
+}
 
 /// Enumerates the possible states the [Transceiver] can be in
 ///
-/// Make sure that the order is EXACTLY the same as in [STATES]
+/// Make sure that the order is EXACTLY the same as in [States::TABLE]
 #[repr(usize)]
 #[derive(Clone, PartialEq, Debug)]
 pub enum State {
@@ -47,8 +73,17 @@ pub enum State {
     MEMTxPayload,
     SendCRC,
     WaitForCRC,
+    AssignAddress,
+    MEMLogicalPayload,
+    MEMPosition,
+    MEMAckSlots,
+    Search,
+    SearchResponse,
+    Status,
+    StatusSlots,
+    StatusResponse,
 }
 
-pub fn handle(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
-    STATES[t.state.clone() as usize](t, rx)
+pub fn handle<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
+    States::<M>::TABLE[t.state.clone() as usize](t, rx)
 }
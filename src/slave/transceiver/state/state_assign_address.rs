@@ -0,0 +1,32 @@
+use crate::slave::transceiver::{state::State, Consequence, SlaveMemory, Transceiver};
+
+pub fn state_assign_address<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
+    if let Some(rx) = rx {
+        t.update_crc(rx);
+
+        // The physical address of the targeted slave
+        // is followed by the new logical address
+        if t.pos < 6 {
+            t.mem_cmd_addr[t.pos as usize] = rx;
+        } else {
+            t.new_logical_address[t.pos as usize - 6] = rx;
+        }
+
+        t.pos += 1;
+
+        if t.pos >= 8 {
+            t.pos = 0;
+            t.state = State::WaitForCRC;
+
+            // Only the slave with the matching physical
+            // address takes over the logical address
+            t.consequence = if t.mem_cmd_addr == t.physical_address {
+                Consequence::AssignLogicalAddress
+            } else {
+                Consequence::None
+            };
+        }
+    }
+
+    None
+}
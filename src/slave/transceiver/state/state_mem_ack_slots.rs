@@ -0,0 +1,33 @@
+use crate::{
+    slave::transceiver::{
+        state::{state_wait_for_start::state_wait_for_start, State},
+        SlaveMemory, Transceiver,
+    },
+    START_BYTE,
+};
+
+pub fn state_mem_ack_slots<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
+    match rx {
+        // Acks are `0` or `1`, so the master gave up on
+        // slots that have been left empty and starts over
+        Some(START_BYTE) => {
+            t.state = State::WaitForStart;
+            state_wait_for_start(t, rx)
+        }
+        Some(_) => {
+            t.next_ack_slot();
+            None
+        }
+        // Every slot is filled by exactly one byte, we acknowledge in ours
+        None if t.pos == t.ack_slot as u16 => {
+            t.next_ack_slot();
+            Some(t.acked as u8)
+        }
+        // On a packet-oriented link, nobody else fills the other slots
+        None if t.framed => {
+            t.next_ack_slot();
+            Some(0)
+        }
+        None => None,
+    }
+}
@@ -1,40 +1,57 @@
 use crate::{
     crc8::CRC,
-    slave::transceiver::{state::State, CallbackAction, Transceiver},
+    slave::transceiver::{
+        state::{state_mem_logical_payload::state_mem_logical_payload, State},
+        system::count,
+        Consequence, SlaveMemory, SyncLossReason, Transceiver,
+    },
 };
 
-pub fn state_mem_header_crc(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
+pub fn state_mem_header_crc<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
     if let Some(rx) = rx {
         if t.crc.finalize() == rx {
-            t.activity_flag = true;
+            t.valid_activity();
+            t.update_crc(rx);
+            t.pos = 0;
+
+            if !t.is_targeted() {
+                return handle_not_targeted(t);
+            }
+
+            // We cannot respond with more data than fits into our
+            // scratchpad, so we treat this like an illegal operation
+            if t.exceeds_scratchpad() {
+                t.loose_sync(SyncLossReason::ScratchpadOverflow);
+                t.state = State::WaitForStart;
+                return None;
+            }
 
             // Call the application to fill the buffer for us
-            let res = (t.callback)(CallbackAction::ReadMemory {
-                offset: t.mem_cmd_offset,
-                data: &mut t.scratchpad[0..(t.mem_cmd_size as usize)],
-            });
+            let res = t.load_scratchpad();
 
             // Check the return code. If it is not ok,
             // we loose sync with the bus, as an illegal
             // operation was performed
             if res.is_err() {
-                t.loose_sync();
+                t.loose_sync(SyncLossReason::CallbackError);
                 t.state = State::WaitForStart;
                 return None;
             }
 
-            t.update_crc(rx);
-            t.pos = 0;
+            // If another slave sends the CRC of a logical read,
+            // our part is reported once the CRC has been received
+            t.consequence = Consequence::ReadServed;
 
-            return if t.is_targeted() {
-                handle_targeted(t)
+            return if t.is_logical_memory_cmd() {
+                t.state = State::MEMLogicalPayload;
+                state_mem_logical_payload(t, None)
             } else {
-                handle_not_targeted(t)
+                handle_targeted(t)
             };
         } else {
             // If we do not match the CRC, we loose sync
             // with the bus and go back to idle
-            t.loose_sync();
+            t.loose_sync(SyncLossReason::HeaderCrc);
             t.state = State::WaitForStart;
         }
     }
@@ -42,11 +59,13 @@ pub fn state_mem_header_crc(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
     None
 }
 
-fn handle_targeted(t: &mut Transceiver) -> Option<u8> {
+fn handle_targeted<M: SlaveMemory>(t: &mut Transceiver<M>) -> Option<u8> {
     match t.mem_cmd_size {
         // A zero-length command results in an immediate CRC
         0 => {
-            t.state = State::WaitForStart;
+            count(&mut t.system.diagnostics.valid_frames);
+            t.read_served();
+            t.state = t.frame_end();
             Some(t.crc.finalize())
         }
         // A one-length read results in the one byte and then the CRC
@@ -66,7 +85,7 @@ fn handle_targeted(t: &mut Transceiver) -> Option<u8> {
     }
 }
 
-fn handle_not_targeted(t: &mut Transceiver) -> Option<u8> {
+fn handle_not_targeted<M: SlaveMemory>(t: &mut Transceiver<M>) -> Option<u8> {
     t.state = match t.mem_cmd_size {
         0 => State::WaitForCRC,
         _ => State::MEMRxPayload,
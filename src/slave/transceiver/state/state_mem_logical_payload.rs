@@ -0,0 +1,54 @@
+use crate::slave::transceiver::{state::State, SlaveMemory, SyncLossReason, Transceiver};
+
+/// Handles the payload of a logical memory read. Every byte is either
+/// sent by us, if it is mapped by our MMU, or received from another slave.
+pub fn state_mem_logical_payload<M: SlaveMemory>(
+    t: &mut Transceiver<M>,
+    rx: Option<u8>,
+) -> Option<u8> {
+    let logical = t.mem_cmd_offset as u32 + t.pos as u32;
+
+    match scratchpad_pos(t, logical) {
+        Some(pos) => {
+            // Somebody else is transmitting in our slot
+            if rx.is_some() {
+                t.loose_sync(SyncLossReason::UnexpectedRx);
+                t.state = State::WaitForStart;
+                return None;
+            }
+
+            let tx_data = t.scratchpad[pos];
+            t.update_crc(tx_data);
+            advance(t);
+            Some(tx_data)
+        }
+        None => {
+            if let Some(rx) = rx {
+                t.update_crc(rx);
+                advance(t);
+            }
+            None
+        }
+    }
+}
+
+fn scratchpad_pos<M: SlaveMemory>(t: &Transceiver<M>, logical: u32) -> Option<usize> {
+    t.system
+        .mmu
+        .scratchpad_pos(t.mem_cmd_offset, t.mem_cmd_size, false, logical)
+}
+
+fn advance<M: SlaveMemory>(t: &mut Transceiver<M>) {
+    t.pos += 1;
+
+    if t.pos >= t.mem_cmd_size {
+        // The slave that sent the last byte of the payload
+        // sends the CRC, all others wait for it
+        let last = t.mem_cmd_offset as u32 + t.mem_cmd_size as u32 - 1;
+        t.state = if scratchpad_pos(t, last).is_some() {
+            State::SendCRC
+        } else {
+            State::WaitForCRC
+        };
+    }
+}
@@ -1,6 +1,6 @@
-use crate::slave::transceiver::{state::State, Transceiver};
+use crate::slave::transceiver::{state::State, SlaveMemory, Transceiver};
 
-pub fn state_mem_offset(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
+pub fn state_mem_offset<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
     if let Some(rx) = rx {
         t.update_crc(rx);
 
@@ -0,0 +1,20 @@
+use crate::slave::transceiver::{state::State, SlaveMemory, Transceiver};
+
+pub fn state_mem_position<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
+    if let Some(rx) = rx {
+        t.update_crc(rx);
+
+        let mut value = t.mem_cmd_position.unwrap_or(0).to_le_bytes();
+        value[t.pos as usize] = rx;
+        t.mem_cmd_position = Some(u16::from_le_bytes(value));
+
+        t.pos += 1;
+
+        if t.pos >= 2 {
+            t.pos = 0;
+            t.state = State::MEMOffset;
+        }
+    }
+
+    None
+}
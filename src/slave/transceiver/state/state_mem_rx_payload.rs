@@ -1,10 +1,28 @@
-use crate::slave::transceiver::{state::State, Consequence, Transceiver};
+use crate::slave::transceiver::{state::State, Consequence, SlaveMemory, Transceiver};
 
-pub fn state_mem_rx_payload(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
+pub fn state_mem_rx_payload<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
     if let Some(rx) = rx {
         t.update_crc(rx);
 
-        t.scratchpad[t.pos as usize] = rx;
+        if t.is_logical_memory_cmd() {
+            // Only writes store the bytes that are mapped by our MMU, the
+            // payload of a read is sent by other slaves and only needs the CRC
+            if t.cur_cmd.mem_is_write_cmd() {
+                let logical = t.mem_cmd_offset as u32 + t.pos as u32;
+                for w in t.system.mmu.windows(t.mem_cmd_offset, t.mem_cmd_size, true) {
+                    if w.scratchpad as usize + w.len as usize > t.scratchpad.len() {
+                        continue;
+                    }
+                    if let Some(pos) = w.scratchpad_pos(logical) {
+                        t.scratchpad[pos] = rx;
+                    }
+                }
+            }
+        } else if t.is_targeted() {
+            // Payloads for other slaves are only checked
+            // using the CRC, but never buffered
+            t.scratchpad[t.pos as usize] = rx;
+        }
         t.pos += 1;
 
         if t.pos >= t.mem_cmd_size {
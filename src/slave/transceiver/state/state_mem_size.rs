@@ -1,6 +1,6 @@
-use crate::slave::transceiver::{state::State, Transceiver};
+use crate::slave::transceiver::{state::State, SlaveMemory, SyncLossReason, Transceiver};
 
-pub fn state_mem_size(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
+pub fn state_mem_size<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
     if let Some(rx) = rx {
         t.update_crc(rx);
 
@@ -14,6 +14,15 @@ pub fn state_mem_size(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
 
         if !(long_size && t.pos < 2) {
             t.pos = 0;
+
+            // A write that targets us but does not fit into our
+            // scratchpad cannot be handled, so we drop out early
+            if t.cur_cmd.mem_is_write_cmd() && t.is_targeted() && t.exceeds_scratchpad() {
+                t.loose_sync(SyncLossReason::ScratchpadOverflow);
+                t.state = State::WaitForStart;
+                return None;
+            }
+
             t.state = if t.mem_cmd_size > 0 {
                 if t.cur_cmd.mem_is_write_cmd() {
                     State::MEMRxPayload
@@ -1,9 +1,9 @@
-use crate::slave::transceiver::{state::State, Transceiver};
+use crate::slave::transceiver::{state::State, SlaveMemory, SyncLossReason, Transceiver};
 
-pub fn state_mem_tx_payload(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
+pub fn state_mem_tx_payload<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
     // We NEVER expect any data
     if rx.is_some() {
-        t.loose_sync();
+        t.loose_sync(SyncLossReason::UnexpectedRx);
         None
     } else {
         let tx_data = t.scratchpad[t.pos as usize];
@@ -0,0 +1,30 @@
+use crate::slave::transceiver::{state::State, Consequence, SlaveMemory, Transceiver};
+
+pub fn state_search<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
+    if let Some(rx) = rx {
+        t.update_crc(rx);
+
+        // The length of the prefix in bits is followed by the prefix
+        if t.pos == 0 {
+            t.search_len = rx;
+        } else {
+            t.mem_cmd_addr[t.pos as usize - 1] = rx;
+        }
+
+        t.pos += 1;
+
+        if t.pos >= 7 {
+            t.pos = 0;
+            t.state = State::WaitForCRC;
+
+            // Only the slaves with a matching physical address respond
+            t.consequence = if t.search_matches() {
+                Consequence::SearchResponse
+            } else {
+                Consequence::None
+            };
+        }
+    }
+
+    None
+}
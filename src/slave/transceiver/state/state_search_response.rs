@@ -0,0 +1,18 @@
+use crate::{
+    slave::transceiver::{
+        state::{state_wait_for_start::state_wait_for_start, State},
+        SlaveMemory, Transceiver,
+    },
+    SEARCH_RESPONSE,
+};
+
+pub fn state_search_response<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
+    t.state = State::WaitForStart;
+
+    // Multiple slaves may match, so another one may
+    // have responded already, which is not an error
+    match rx {
+        Some(rx) => state_wait_for_start(t, Some(rx)),
+        None => Some(SEARCH_RESPONSE),
+    }
+}
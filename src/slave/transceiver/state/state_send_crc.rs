@@ -1,17 +1,20 @@
 use crate::{
     crc8::CRC,
-    slave::transceiver::{state::State, Transceiver},
+    slave::transceiver::{state::State, system::count, SlaveMemory, SyncLossReason, Transceiver},
 };
 
-pub fn state_send_crc(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
+pub fn state_send_crc<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
     // In either way we end up in the idle state after this state
     t.state = State::WaitForStart;
 
     // We NEVER expect any data
     if rx.is_some() {
-        t.loose_sync();
+        t.loose_sync(SyncLossReason::UnexpectedRx);
         None
     } else {
+        count(&mut t.system.diagnostics.valid_frames);
+        t.read_served();
+        t.state = t.frame_end();
         Some(t.crc.finalize())
     }
 }
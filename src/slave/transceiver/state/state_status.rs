@@ -0,0 +1,40 @@
+use crate::slave::transceiver::{state::State, Consequence, SlaveMemory, Transceiver};
+
+pub fn state_status<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
+    if let Some(rx) = rx {
+        t.update_crc(rx);
+
+        // The first logical address, the amount of slots, the slot
+        // time, the offset and the size are collected before parsing
+        match t.pos {
+            0..=4 => t.mem_cmd_addr[t.pos as usize] = rx,
+            5 => t.mem_cmd_offset = rx as u16,
+            6 => t.mem_cmd_offset |= (rx as u16) << 8,
+            _ => t.mem_cmd_size = rx as u16,
+        }
+
+        t.pos += 1;
+
+        if t.pos >= 8 {
+            t.pos = 0;
+            t.state = State::WaitForCRC;
+
+            let first = u16::from_le_bytes([t.mem_cmd_addr[0], t.mem_cmd_addr[1]]);
+            t.status_slots = t.mem_cmd_addr[2];
+            t.slot_time = u16::from_le_bytes([t.mem_cmd_addr[3], t.mem_cmd_addr[4]]);
+
+            // All slaves taking part wait for the slots to pass, but
+            // only the ones with a slot of their own respond. The others
+            // cannot time the slots and return to idle right away.
+            let slot = u16::from_le_bytes(t.logical_address).wrapping_sub(first);
+            t.status_slot = (slot < t.status_slots as u16).then_some(slot as u8);
+            t.consequence = if t.status_read {
+                Consequence::StatusResponse
+            } else {
+                Consequence::None
+            };
+        }
+    }
+
+    None
+}
@@ -0,0 +1,23 @@
+use crate::slave::transceiver::{state::State, SlaveMemory, SyncLossReason, Transceiver};
+
+pub fn state_status_response<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
+    // Another slave responds in our slot, so the slots are misconfigured
+    if rx.is_some() {
+        t.loose_sync(SyncLossReason::UnexpectedRx);
+        t.state = State::WaitForStart;
+        return None;
+    }
+
+    // Our logical address and the data are followed by their CRC
+    let tx = t.scratchpad[t.pos as usize];
+    t.pos += 1;
+
+    if t.pos >= t.mem_cmd_size + 3 {
+        t.pos = 0;
+        t.status_slot = None;
+        t.state = State::StatusSlots;
+        t.read_served();
+    }
+
+    Some(tx)
+}
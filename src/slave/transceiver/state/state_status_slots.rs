@@ -0,0 +1,7 @@
+use crate::slave::transceiver::{SlaveMemory, Transceiver};
+
+pub fn state_status_slots<M: SlaveMemory>(_t: &mut Transceiver<M>, _rx: Option<u8>) -> Option<u8> {
+    // The responses of the other slaves are of no interest to us.
+    // The slots are timed by the ticks, which end them eventually.
+    None
+}
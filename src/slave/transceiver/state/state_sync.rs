@@ -1,22 +1,21 @@
 use crate::{
-    slave::transceiver::{state::State, Consequence, Transceiver},
-    test_log, PROTOCOL_VERSION_1, SYNC_SEQUENCE,
+    slave::transceiver::{state::State, Consequence, SlaveMemory, SyncLossReason, Transceiver},
+    PROTOCOL_VERSION_1, SYNC_SEQUENCE,
 };
 
-pub fn state_sync(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
+pub fn state_sync<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
     if let Some(rx) = rx {
         t.update_crc(rx);
 
         if t.pos <= 14 {
             if rx != SYNC_SEQUENCE[t.pos as usize] {
-                test_log!("Lost sync!");
-                t.loose_sync();
+                t.loose_sync(SyncLossReason::UnexpectedRx);
                 t.state = State::WaitForStart;
             }
         } else if t.pos >= 15 {
             let version = rx;
             if version != PROTOCOL_VERSION_1 {
-                t.loose_sync();
+                t.loose_sync(SyncLossReason::VersionMismatch);
             } else {
                 t.consequence = Consequence::GainSync;
             }
@@ -2,15 +2,16 @@ use crate::{
     slave::transceiver::{
         command::{AddressingMode, Command},
         state::State,
-        Transceiver,
+        Consequence, SlaveMemory, SyncLossReason, Transceiver,
     },
-    test_log, CMD_NOP, CMD_SYNC,
+    test_log, CMD_ASSIGN_LOGICAL_ADDRESS, CMD_NOP, CMD_POSITION_READ, CMD_POSITION_WRITE,
+    CMD_RESET, CMD_SEARCH, CMD_STATUS_READ, CMD_SYNC,
 };
 
 const MASK_CMD_COMMAND: u8 = 0b11_1111;
 const MASK_CMD_SEQUENCE: u8 = 0b1100_0000;
 
-pub fn state_wait_for_cmd(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
+pub fn state_wait_for_cmd<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
     if let Some(rx) = rx {
         t.update_crc(rx);
 
@@ -21,7 +22,7 @@ pub fn state_wait_for_cmd(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
         // If the sequence numbers don't match up and we're already
         // in sync, we've lost something and we loose sync with the bus
         if (t.sequence_no + 1) & 0b11 != seq && t.in_sync {
-            t.loose_sync();
+            t.loose_sync(SyncLossReason::Sequence);
             t.state = State::WaitForStart;
             return None;
         }
@@ -32,20 +33,56 @@ pub fn state_wait_for_cmd(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
 
         // Set the internal command for later use
         t.cur_cmd = Command::new(rx);
+        t.mem_cmd_position = None;
 
         // Match on the command to determine which state to transition to.
         let state = match cmd {
             CMD_NOP => State::WaitForCRC,
             CMD_SYNC => State::Sync,
-            0b1_00000..0b1_11111 => handle_mem_cmd(t),
+            CMD_RESET => {
+                t.consequence = Consequence::Reset;
+                State::WaitForCRC
+            }
+            CMD_ASSIGN_LOGICAL_ADDRESS => {
+                t.pos = 0;
+                State::AssignAddress
+            }
+            CMD_POSITION_READ | CMD_POSITION_WRITE => {
+                // Position addressed commands continue like broadcast memory
+                // commands using 16-bit offsets and sizes after the position
+                let write = cmd == CMD_POSITION_WRITE;
+                t.cur_cmd = Command::new_mem(seq, write, 0, true, true);
+                t.mem_cmd_position = Some(0);
+                t.pos = 0;
+                State::MEMPosition
+            }
+            CMD_SEARCH => {
+                t.pos = 0;
+                State::Search
+            }
+            CMD_STATUS_READ => {
+                t.pos = 0;
+                State::Status
+            }
+            0b1_00000..=0b1_11111 => handle_mem_cmd(t),
             _ => {
                 // An unknown command has been received.
                 // In that case, we loose sync and go back to idle
-                t.loose_sync();
+                t.loose_sync(SyncLossReason::UnknownCommand);
                 State::WaitForStart
             }
         };
 
+        // Memory commands are followed by the ack slots of the working
+        // counter, the configuration may change during the command
+        t.acked = false;
+        t.ack_slots = if t.cur_cmd.is_mem_cmd() {
+            t.system.ack_slots
+        } else {
+            0
+        };
+        t.ack_slot = t.system.ack_slot;
+
         // If we are NOT in sync, there is only one allowed
         // next state: sync, otherwise, we'll go back to idle
         // as we are not in sync with the bus and the data we
@@ -62,7 +99,7 @@ pub fn state_wait_for_cmd(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
     None
 }
 
-fn handle_mem_cmd(t: &mut Transceiver) -> State {
+fn handle_mem_cmd<M: SlaveMemory>(t: &mut Transceiver<M>) -> State {
     t.pos = 0;
     test_log!(
         "Addressing mode: {:?}",
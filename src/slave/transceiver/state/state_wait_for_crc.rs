@@ -1,19 +1,34 @@
 use crate::{
     crc8::CRC,
-    slave::transceiver::{state::State, CallbackAction, Consequence, Transceiver},
+    slave::transceiver::{
+        state::State, system::count, Consequence, Event, SlaveMemory, SyncLossReason, Transceiver,
+    },
 };
 
-pub fn state_wait_for_crc(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
+pub fn state_wait_for_crc<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
     if let Some(rx) = rx {
         t.state = if t.crc.finalize() == rx {
-            t.activity_flag = true;
-            handle_consequence(t);
+            t.valid_activity();
 
-            State::WaitForStart
+            // A frame whose consequence failed is not counted as valid
+            if handle_consequence(t) {
+                count(&mut t.system.diagnostics.valid_frames);
+
+                match t.consequence {
+                    Consequence::SearchResponse => State::SearchResponse,
+                    Consequence::StatusResponse => {
+                        t.slots_start = None;
+                        State::StatusSlots
+                    }
+                    _ => t.frame_end(),
+                }
+            } else {
+                State::WaitForStart
+            }
         } else {
             // If we do not match the CRC, we loose sync
             // with the bus and go back to idle
-            t.loose_sync();
+            t.loose_sync(SyncLossReason::FrameCrc);
             State::WaitForStart
         };
     }
@@ -21,7 +36,10 @@ pub fn state_wait_for_crc(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
     None
 }
 
-fn handle_consequence(t: &mut Transceiver) {
+/// Applies the consequence of the frame
+/// # Returns
+/// Whether the consequence succeeded, otherwise we lost sync
+fn handle_consequence<M: SlaveMemory>(t: &mut Transceiver<M>) -> bool {
     match t.consequence {
         // No consequence, just do nothing
         Consequence::None => {}
@@ -30,24 +48,55 @@ fn handle_consequence(t: &mut Transceiver) {
         // sync command into the internal sync register
         // and go into the synchronized state
         Consequence::GainSync => {
-            t.in_sync = true;
+            t.gain_sync();
             t.sequence_no = (t.cur_cmd.raw() >> 6) & 0b11;
         }
 
         // Write the contents of the scratchpad to memory
         Consequence::WriteScratchpad => {
-            let res = (t.callback)(CallbackAction::WriteMemory {
-                offset: t.mem_cmd_offset,
-                data: &mut t.scratchpad[0..t.mem_cmd_size as usize],
-            });
+            let res = t.commit_scratchpad();
 
-            // If the callback was not successful
+            // If the memory could not be written
             // we loose sync with the bus, as an
             // illegal operation took place
             if res.is_err() {
-                t.loose_sync();
-                t.state = State::WaitForStart;
+                t.loose_sync(SyncLossReason::CallbackError);
+                return false;
+            }
+
+            t.acked = true;
+            t.memory.event(Event::WriteCommitted {
+                offset: t.mem_cmd_offset,
+                len: t.mem_cmd_size,
+            });
+        }
+
+        // Reset the configuration to the boot state and
+        // let the application reset its memory area
+        Consequence::Reset => {
+            t.logical_address = [0u8; 2];
+            t.system.reset();
+
+            if t.memory.reset().is_err() {
+                t.loose_sync(SyncLossReason::CallbackError);
+                return false;
             }
         }
+
+        // Take over the logical address the master assigned to us
+        Consequence::AssignLogicalAddress => {
+            t.logical_address = t.new_logical_address;
+        }
+
+        // Our part of a read has been confirmed by the CRC of another slave
+        Consequence::ReadServed => t.read_served(),
+
+        // We respond in the state following the CRC
+        Consequence::SearchResponse => {}
+
+        // Prepare our response, which is sent once our slot has come
+        Consequence::StatusResponse => t.prepare_status(),
     }
+
+    true
 }
@@ -1,10 +1,10 @@
 use crate::{
     crc8::{CRC8Autosar, CRC},
-    slave::transceiver::{state::State, Consequence, Transceiver},
+    slave::transceiver::{state::State, Consequence, SlaveMemory, Transceiver},
     START_BYTE,
 };
 
-pub fn state_wait_for_start(t: &mut Transceiver, rx: Option<u8>) -> Option<u8> {
+pub fn state_wait_for_start<M: SlaveMemory>(t: &mut Transceiver<M>, rx: Option<u8>) -> Option<u8> {
     if let Some(rx) = rx {
         if rx == START_BYTE {
             t.state = State::WaitForCommand;
@@ -0,0 +1,170 @@
+use core::time::Duration;
+
+use crate::{
+    slave::transceiver::{mmu::Mmu, MemoryError},
+    system::{
+        Diagnostics, FeatureSupport, SlaveCapabilities, CAPABILITIES_OFFSET, CAPABILITIES_SIZE,
+        DIAGNOSTICS_OFFSET, DIAGNOSTICS_SIZE, MMU_COUNT, MMU_ENTRY_SIZE, MMU_OFFSET,
+        SYSTEM_REGION_START, WATCHDOG_OFFSET, WATCHDOG_SIZE, WORKING_COUNTER_OFFSET,
+    },
+    PROTOCOL_VERSION_1,
+};
+
+const SYSTEM_REGION_SIZE: usize = 0x100;
+
+/// The state of the system region that is
+/// served by the transceiver itself
+pub struct System {
+    pub mmu: Mmu,
+    pub diagnostics: Diagnostics,
+    /// The watchdog timeout in milliseconds, `0` if disabled
+    pub watchdog: u16,
+    /// The amount of ack slots following every memory command, `0` if disabled
+    pub ack_slots: u8,
+    /// The ack slot we acknowledge memory commands in
+    pub ack_slot: u8,
+    scratchpad_size: u16,
+}
+
+impl System {
+    pub const fn new(scratchpad_size: usize) -> Self {
+        Self {
+            mmu: Mmu::new(),
+            diagnostics: Diagnostics {
+                valid_frames: 0,
+                header_crc_errors: 0,
+                frame_crc_errors: 0,
+                sequence_errors: 0,
+                unknown_commands: 0,
+                memory_errors: 0,
+                sync_gained: 0,
+                sync_lost: 0,
+            },
+            watchdog: 0,
+            ack_slots: 0,
+            ack_slot: 0,
+            scratchpad_size: if scratchpad_size > u16::MAX as usize {
+                u16::MAX
+            } else {
+                scratchpad_size as u16
+            },
+        }
+    }
+
+    /// Returns the capabilities of the transceiver
+    pub fn capabilities(&self) -> SlaveCapabilities {
+        SlaveCapabilities {
+            protocol_versions: 1 << (PROTOCOL_VERSION_1 - 1),
+            scratchpad_size: self.scratchpad_size,
+            long_offset: FeatureSupport::Full,
+            // Operations beyond 255 bytes can only target us
+            // if the scratchpad is large enough to hold them
+            long_size: if self.scratchpad_size > 0xFF {
+                FeatureSupport::Full
+            } else {
+                FeatureSupport::Partial
+            },
+            logical_addressing: FeatureSupport::Full,
+            mmu: FeatureSupport::Full,
+            mmu_count: MMU_COUNT as u8,
+        }
+    }
+
+    /// Resets the system region to its boot state.
+    ///
+    /// The diagnostics counters are kept, as they describe the link.
+    pub fn reset(&mut self) {
+        self.mmu.clear();
+        self.watchdog = 0;
+        self.ack_slots = 0;
+        self.ack_slot = 0;
+    }
+
+    /// Returns the watchdog timeout, if the watchdog is enabled
+    pub fn watchdog_timeout(&self) -> Option<Duration> {
+        match self.watchdog {
+            0 => None,
+            ms => Some(Duration::from_millis(ms as u64)),
+        }
+    }
+
+    /// Reads from the system region.
+    ///
+    /// Reserved bytes read as `0`.
+    pub fn read(&self, offset: u16, data: &mut [u8]) -> Result<(), MemoryError> {
+        let image = self.image();
+        let start = (offset - SYSTEM_REGION_START) as usize;
+        let src = image
+            .get(start..start + data.len())
+            .ok_or(MemoryError::OutOfRange)?;
+        data.copy_from_slice(src);
+        Ok(())
+    }
+
+    /// Writes to the system region.
+    ///
+    /// Writes are only allowed to the writable blocks
+    /// and are rejected everywhere else.
+    pub fn write(&mut self, offset: u16, data: &[u8]) -> Result<(), MemoryError> {
+        let mut watchdog = self.watchdog.to_le_bytes();
+        if let Some(dst) = block(WATCHDOG_OFFSET, &mut watchdog, offset, data.len()) {
+            dst.copy_from_slice(data);
+            self.watchdog = u16::from_le_bytes(watchdog);
+            return Ok(());
+        }
+
+        let mut working_counter = [self.ack_slots, self.ack_slot];
+        if let Some(dst) = block(
+            WORKING_COUNTER_OFFSET,
+            &mut working_counter,
+            offset,
+            data.len(),
+        ) {
+            dst.copy_from_slice(data);
+            [self.ack_slots, self.ack_slot] = working_counter;
+            return Ok(());
+        }
+
+        let mut table = self.mmu.to_bytes();
+        let dst =
+            block(MMU_OFFSET, &mut table, offset, data.len()).ok_or(MemoryError::AccessDenied)?;
+        dst.copy_from_slice(data);
+        self.mmu.load_bytes(&table);
+        Ok(())
+    }
+
+    /// Returns the contents of the complete system region
+    fn image(&self) -> [u8; SYSTEM_REGION_SIZE] {
+        let mut image = [0u8; SYSTEM_REGION_SIZE];
+
+        let caps = (CAPABILITIES_OFFSET - SYSTEM_REGION_START) as usize;
+        image[caps..caps + CAPABILITIES_SIZE].copy_from_slice(&self.capabilities().to_bytes());
+
+        let diag = (DIAGNOSTICS_OFFSET - SYSTEM_REGION_START) as usize;
+        image[diag..diag + DIAGNOSTICS_SIZE].copy_from_slice(&self.diagnostics.to_bytes());
+
+        let watchdog = (WATCHDOG_OFFSET - SYSTEM_REGION_START) as usize;
+        image[watchdog..watchdog + WATCHDOG_SIZE].copy_from_slice(&self.watchdog.to_le_bytes());
+
+        let wkc = (WORKING_COUNTER_OFFSET - SYSTEM_REGION_START) as usize;
+        image[wkc] = self.ack_slots;
+        image[wkc + 1] = self.ack_slot;
+
+        let mmu = (MMU_OFFSET - SYSTEM_REGION_START) as usize;
+        image[mmu..mmu + MMU_COUNT * MMU_ENTRY_SIZE].copy_from_slice(&self.mmu.to_bytes());
+
+        image
+    }
+}
+
+/// Returns the part of the `block` starting at `block_offset` that
+/// is accessed at `offset` with `len` bytes, if the access fits into it
+fn block(block_offset: u16, block: &mut [u8], offset: u16, len: usize) -> Option<&mut [u8]> {
+    let start = offset.checked_sub(block_offset)? as usize;
+    block.get_mut(start..start + len)
+}
+
+/// Increments a diagnostics counter, wrapping around on overflow
+pub fn count(counter: &mut u32) {
+    *counter = counter.wrapping_add(1);
+}
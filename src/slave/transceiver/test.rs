@@ -1,11 +1,30 @@
-use crate::slave::transceiver::{state::State, CallbackAction};
+use core::time::Duration;
+
+use crate::{
+    frame::Frame,
+    slave::transceiver::{state::State, CallbackAction, Event, MemoryError, SlaveMemory},
+};
 
 //mod mem_cmd;
+mod t_cmd_assign_address;
 mod t_cmd_mem_addressed;
 mod t_cmd_mem_broadcast;
+mod t_cmd_mem_logical;
 mod t_cmd_nop;
+mod t_cmd_reset;
+mod t_cmd_search;
+mod t_cmd_status;
 mod t_cmd_sync;
+mod t_diagnostics;
+mod t_forward;
+mod t_framed;
+mod t_partial_support;
 mod t_sequence;
+mod t_slave_memory;
+mod t_system_capabilities;
+mod t_timeouts;
+mod t_watchdog;
+mod t_working_counter;
 
 /// Test that the supplied transceiver is in the correct state
 macro_rules! test_state {
@@ -75,6 +94,88 @@ macro_rules! new_transceiver_in_sync {
     };
 }
 
+/// The physical address of the transceiver under test
+pub(crate) const ADDR: [u8; 6] = [1, 2, 3, 4, 5, 6];
+
+/// A memory area of 8 bytes that records resets and events
+pub(crate) struct TestMemory {
+    pub data: [u8; 8],
+    pub resets: usize,
+    pub events: Vec<Event>,
+}
+
+impl TestMemory {
+    pub fn new(data: [u8; 8]) -> Self {
+        Self {
+            data,
+            resets: 0,
+            events: Vec::new(),
+        }
+    }
+}
+
+impl SlaveMemory for TestMemory {
+    fn read(&mut self, offset: u16, data: &mut [u8]) -> Result<(), MemoryError> {
+        let start = offset as usize;
+        let src = self
+            .data
+            .get(start..start + data.len())
+            .ok_or(MemoryError::OutOfRange)?;
+        data.copy_from_slice(src);
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u16, data: &[u8]) -> Result<(), MemoryError> {
+        let start = offset as usize;
+        let dst = self
+            .data
+            .get_mut(start..start + data.len())
+            .ok_or(MemoryError::OutOfRange)?;
+        dst.copy_from_slice(data);
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<(), MemoryError> {
+        self.data = [0u8; 8];
+        self.resets += 1;
+        Ok(())
+    }
+
+    fn event(&mut self, event: Event) {
+        self.events.push(event);
+    }
+}
+
+/// Create a new transceiver with the physical address [ADDR] operating
+/// on a [TestMemory] holding `data`, which is not in sync unless `in_sync`
+macro_rules! new_transceiver {
+    ($t: ident, $data: expr) => {
+        let mut scratchpad = [0u8; 0xf];
+        let mut $t = crate::slave::transceiver::Transceiver::with_memory(
+            &mut scratchpad,
+            crate::slave::transceiver::test::ADDR,
+            crate::slave::transceiver::test::TestMemory::new($data),
+        );
+    };
+    ($t: ident, $data: expr, in_sync) => {
+        new_transceiver!($t, $data);
+        $t.in_sync = true;
+        $t.sequence_no = 0b11;
+    };
+}
+
+/// Encodes the `frame` using the sequence number `seq`
+pub(crate) fn encode(frame: &Frame, seq: u8) -> Vec<u8> {
+    let mut data = vec![0u8; frame.encoded_len().unwrap()];
+    frame.encode(seq, &mut data).unwrap();
+    data
+}
+
+/// Returns a duration of `ms` milliseconds
+pub(crate) fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
 macro_rules! test_rx_crc_no_response {
     ($t: expr) => {
         let crc = $crate::crc8::CRC::finalize(&$t.crc);
@@ -93,8 +194,8 @@ macro_rules! test_consequence {
 }
 
 pub(crate) use {
-    new_transceiver_in_sync, test_consequence, test_rx_crc_no_response, test_rx_no_response,
-    test_state, test_sync, test_tx,
+    new_transceiver, new_transceiver_in_sync, test_consequence, test_rx_crc_no_response,
+    test_rx_no_response, test_state, test_sync, test_tx,
 };
 
 #[test]
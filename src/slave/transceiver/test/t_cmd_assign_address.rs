@@ -0,0 +1,66 @@
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    slave::transceiver::{
+        test::{
+            new_transceiver_in_sync, test_consequence, test_rx_no_response, test_state, test_sync,
+        },
+        Consequence, State,
+    },
+    CMD_ASSIGN_LOGICAL_ADDRESS, START_BYTE,
+};
+
+macro_rules! test_assign {
+    ($t: ident, $data: expr, $consequence: expr, $addr: expr) => {
+        let crc = CRC8Autosar::new().update_move(&$data).finalize();
+        new_transceiver_in_sync!($t, $addr);
+        $t.set_logical_address([0xAA, 0xBB]);
+        for b in $data {
+            test_rx_no_response!($t, b);
+        }
+
+        test_consequence!($t, $consequence);
+        test_rx_no_response!($t, crc);
+        test_state!($t, State::WaitForStart);
+        test_sync!($t, true);
+    };
+}
+
+#[test]
+fn assign_addressed() {
+    let addr = [1, 2, 3, 4, 5, 6];
+    let data = vec![
+        START_BYTE,
+        CMD_ASSIGN_LOGICAL_ADDRESS,
+        1,
+        2,
+        3,
+        4,
+        5,
+        6,
+        0x12,
+        0x34,
+    ];
+
+    test_assign!(t, data, Consequence::AssignLogicalAddress, addr);
+    assert_eq!(t.logical_address(), [0x12, 0x34]);
+}
+
+#[test]
+fn assign_not_addressed() {
+    let addr = [1, 2, 3, 4, 5, 6];
+    let data = vec![
+        START_BYTE,
+        CMD_ASSIGN_LOGICAL_ADDRESS,
+        6,
+        5,
+        4,
+        3,
+        2,
+        1,
+        0x12,
+        0x34,
+    ];
+
+    test_assign!(t, data, Consequence::None, addr);
+    assert_eq!(t.logical_address(), [0xAA, 0xBB]);
+}
@@ -0,0 +1,247 @@
+use std::cell::RefCell;
+
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    frame::{Frame, SlaveAddress},
+    slave::transceiver::{
+        test::{encode, test_rx_no_response, test_state, test_sync},
+        CallbackAction, Event, State, Transceiver,
+    },
+    system::{MmuEntry, MMU_ENTRY_SIZE, MMU_OFFSET},
+};
+
+thread_local! {
+    static WRITES: RefCell<Vec<(u16, Vec<u8>)>> = const { RefCell::new(Vec::new()) };
+    static EVENTS: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records all writes and events and answers reads with the offset of each byte
+fn recording_callback(action: CallbackAction) -> Result<(), ()> {
+    match action {
+        CallbackAction::WriteMemory { offset, data } => {
+            WRITES.with_borrow_mut(|w| w.push((offset, data.to_vec())))
+        }
+        CallbackAction::ReadMemory { offset, data } => {
+            for (i, d) in data.iter_mut().enumerate() {
+                *d = offset as u8 + i as u8;
+            }
+        }
+        CallbackAction::Reset => {}
+        CallbackAction::Event(event) => EVENTS.with_borrow_mut(|e| e.push(event)),
+    }
+    Ok(())
+}
+
+fn entry(logical_start: u16, length: u16, physical_start: u16) -> Option<MmuEntry> {
+    Some(MmuEntry {
+        logical_start,
+        length,
+        physical_start,
+        read: true,
+        write: true,
+    })
+}
+
+/// Feeds the `data` of the master into all slaves and lets the slaves
+/// respond to each other, as on a shared medium. Returns the bytes sent by the slaves.
+fn run_bus(slaves: &mut [Transceiver], data: &[u8]) -> Vec<u8> {
+    let mut bus = Vec::new();
+    let mut pending: Vec<Option<u8>> = slaves.iter_mut().map(|s| s.handle(None)).collect();
+
+    for b in data {
+        for (s, p) in slaves.iter_mut().zip(pending.iter_mut()) {
+            *p = s.handle(Some(*b));
+        }
+    }
+
+    loop {
+        for (s, p) in slaves.iter_mut().zip(pending.iter_mut()) {
+            if p.is_none() {
+                *p = s.handle(None);
+            }
+        }
+
+        let senders: Vec<usize> = (0..slaves.len())
+            .filter(|i| pending[*i].is_some())
+            .collect();
+        match senders.as_slice() {
+            [] => break,
+            [sender] => {
+                let b = pending[*sender].take().unwrap();
+                bus.push(b);
+                for (i, s) in slaves.iter_mut().enumerate() {
+                    if i != *sender {
+                        pending[i] = s.handle(Some(b));
+                    }
+                }
+            }
+            _ => panic!("Collision on the bus"),
+        }
+    }
+
+    bus
+}
+
+macro_rules! new_slaves {
+    ($s0: ident, $s1: ident) => {
+        let mut scratchpad0 = [0u8; 0xf];
+        let mut scratchpad1 = [0u8; 0xf];
+        let mut $s0 = Transceiver::new(&mut scratchpad0, [0u8; 6], recording_callback);
+        let mut $s1 = Transceiver::new(&mut scratchpad1, [1u8; 6], recording_callback);
+        for s in [&mut $s0, &mut $s1] {
+            s.in_sync = true;
+            s.sequence_no = 0b11;
+        }
+    };
+}
+
+#[test]
+fn logical_write() {
+    new_slaves!(s0, s1);
+    s0.set_mmu_entry(0, entry(0x100, 2, 0x10));
+    s1.set_mmu_entry(0, entry(0x102, 2, 0x20));
+    s1.set_mmu_entry(1, entry(0x0FF, 1, 0x30));
+
+    let data = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::LogicalMemory,
+            offset: 0x100,
+            payload: &[1, 2, 3, 4, 5],
+        },
+        0,
+    );
+
+    WRITES.with_borrow_mut(|w| w.clear());
+    let mut slaves = [s0, s1];
+    assert!(run_bus(&mut slaves, &data).is_empty());
+
+    // The window at 0x0FF is not part of the operation
+    WRITES.with_borrow(|w| {
+        assert_eq!(w, &vec![(0x10, vec![1, 2]), (0x20, vec![3, 4])]);
+    });
+
+    for s in slaves {
+        test_state!(s, State::WaitForStart);
+        test_sync!(s, true);
+    }
+}
+
+#[test]
+fn logical_write_not_mapped() {
+    new_slaves!(s0, _s1);
+    s0.set_mmu_entry(0, entry(0x200, 2, 0x10));
+
+    let data = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::LogicalMemory,
+            offset: 0x100,
+            payload: &[1, 2],
+        },
+        0,
+    );
+
+    WRITES.with_borrow_mut(|w| w.clear());
+    for b in data {
+        test_rx_no_response!(s0, b);
+    }
+
+    WRITES.with_borrow(|w| assert!(w.is_empty()));
+    test_state!(s0, State::WaitForStart);
+    test_sync!(s0, true);
+}
+
+#[test]
+fn logical_write_long() {
+    new_slaves!(s0, _s1);
+    s0.set_mmu_entry(0, entry(0x1000, 4, 0x10));
+
+    let payload: Vec<u8> = (0..300).map(|i| i as u8).collect();
+    let data = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::LogicalMemory,
+            offset: 0x0F00,
+            payload: &payload,
+        },
+        0,
+    );
+
+    // The logical memory write with a 16-bit offset and size
+    // is the last command of the memory command set
+    assert_eq!(data[1], 0x3F);
+
+    WRITES.with_borrow_mut(|w| w.clear());
+    for b in data {
+        test_rx_no_response!(s0, b);
+    }
+
+    WRITES.with_borrow(|w| assert_eq!(w, &vec![(0x10, payload[0x100..0x104].to_vec())]));
+    test_state!(s0, State::WaitForStart);
+    test_sync!(s0, true);
+    assert_eq!(s0.diagnostics().unknown_commands, 0);
+}
+
+#[test]
+fn logical_read() {
+    new_slaves!(s0, s1);
+    s0.set_mmu_entry(0, entry(0x100, 2, 0x10));
+    s0.set_mmu_entry(1, entry(0x104, 1, 0x50));
+    s1.set_mmu_entry(0, entry(0x102, 2, 0x20));
+
+    let data = encode(
+        &Frame::MemRead {
+            address: SlaveAddress::LogicalMemory,
+            offset: 0x100,
+            size: 5,
+            payload: &[],
+        },
+        0,
+    );
+
+    let mut slaves = [s0, s1];
+    EVENTS.with_borrow_mut(|e| e.clear());
+    let response = run_bus(&mut slaves, &data);
+
+    let payload = [0x10, 0x11, 0x20, 0x21, 0x50];
+    let crc = CRC8Autosar::new()
+        .update_move(&data)
+        .update_move(&payload)
+        .finalize();
+    assert_eq!(&response[..5], &payload);
+    assert_eq!(response[5], crc);
+
+    // Both slaves report the read, no matter which one sent the CRC
+    let served = Event::ReadServed {
+        offset: 0x100,
+        len: 5,
+    };
+    EVENTS.with_borrow(|e| assert_eq!(e, &[served, served]));
+
+    for s in slaves {
+        test_state!(s, State::WaitForStart);
+        test_sync!(s, true);
+    }
+}
+
+#[test]
+fn configure_via_system_region() {
+    new_slaves!(s0, _s1);
+    s0.physical_address = [1, 2, 3, 4, 5, 6];
+
+    let bytes = MmuEntry::to_bytes(entry(0x100, 2, 0x10).as_ref());
+    let data = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::Physical([1, 2, 3, 4, 5, 6]),
+            offset: MMU_OFFSET + MMU_ENTRY_SIZE as u16,
+            payload: &bytes,
+        },
+        0,
+    );
+
+    for b in data {
+        test_rx_no_response!(s0, b);
+    }
+
+    test_sync!(s0, true);
+    assert_eq!(s0.mmu_entry(0), None);
+    assert_eq!(s0.mmu_entry(1), entry(0x100, 2, 0x10));
+}
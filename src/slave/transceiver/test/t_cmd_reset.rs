@@ -0,0 +1,53 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    slave::transceiver::{
+        test::{test_consequence, test_rx_no_response, test_state, test_sync},
+        CallbackAction, Consequence, State, Transceiver,
+    },
+    CMD_RESET, START_BYTE,
+};
+
+static RESETS: AtomicUsize = AtomicUsize::new(0);
+
+fn reset_callback(action: CallbackAction) -> Result<(), ()> {
+    if let CallbackAction::Reset = action {
+        RESETS.fetch_add(1, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+#[test]
+fn cmd_reset() {
+    let mut scratchpad = [0u8; 0xf];
+    let mut t = Transceiver::new(&mut scratchpad, [0u8; 6], reset_callback);
+    t.in_sync = true;
+    t.sequence_no = 0b11;
+    t.logical_address = [5, 6];
+
+    let data = [START_BYTE, CMD_RESET];
+    let crc = CRC8Autosar::new().update_move(&data).finalize();
+
+    // A reset with a broken CRC must not have any effect
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+    test_consequence!(t, Consequence::Reset);
+    test_rx_no_response!(t, !crc);
+    assert_eq!(t.logical_address, [5, 6]);
+    assert_eq!(RESETS.load(Ordering::Relaxed), 0);
+
+    // Regain sync and try again
+    t.in_sync = true;
+    t.sequence_no = 0b11;
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+    test_rx_no_response!(t, crc);
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+    assert_eq!(t.logical_address, [0, 0]);
+    assert_eq!(RESETS.load(Ordering::Relaxed), 1);
+}
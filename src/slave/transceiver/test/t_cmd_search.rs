@@ -0,0 +1,71 @@
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    slave::transceiver::{
+        test::{new_transceiver_in_sync, test_rx_no_response, test_state, test_sync},
+        State,
+    },
+    CMD_SEARCH, SEARCH_RESPONSE, START_BYTE,
+};
+
+const ADDR: [u8; 6] = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC];
+
+/// Run a search for the first `len` bits of `prefix`
+/// # Returns
+/// Whether the slave responded
+fn search(prefix: [u8; 6], len: u8) -> bool {
+    let mut data = vec![START_BYTE, CMD_SEARCH, len];
+    data.extend_from_slice(&prefix);
+    let crc = CRC8Autosar::new().update_move(&data).finalize();
+
+    new_transceiver_in_sync!(t, ADDR);
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+    test_rx_no_response!(t, crc);
+
+    let res = t.handle(None);
+    if let Some(tx) = res {
+        assert_eq!(tx, SEARCH_RESPONSE);
+    }
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+    res.is_some()
+}
+
+#[test]
+fn matching_prefix() {
+    assert!(search([0; 6], 0));
+    assert!(search([0x12, 0, 0, 0, 0, 0], 8));
+    assert!(search([0x12, 0x30, 0, 0, 0, 0], 12));
+    assert!(search([0x12, 0x34, 0x56, 0x78, 0x9A, 0xBD], 47));
+    assert!(search(ADDR, 48));
+    assert!(search(ADDR, 0xFF));
+}
+
+#[test]
+fn mismatching_prefix() {
+    assert!(!search([0x80, 0, 0, 0, 0, 0], 1));
+    assert!(!search([0x12, 0x38, 0, 0, 0, 0], 13));
+    assert!(!search([0x12, 0x34, 0x56, 0x78, 0x9A, 0xBD], 48));
+}
+
+#[test]
+fn other_response() {
+    let mut data = vec![START_BYTE, CMD_SEARCH, 0];
+    data.extend_from_slice(&[0; 6]);
+    let crc = CRC8Autosar::new().update_move(&data).finalize();
+
+    new_transceiver_in_sync!(t, ADDR);
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+    test_rx_no_response!(t, crc);
+
+    // Another slave responded before us
+    test_rx_no_response!(t, SEARCH_RESPONSE);
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+
+    test_rx_no_response!(t, START_BYTE);
+    test_state!(t, State::WaitForCommand);
+}
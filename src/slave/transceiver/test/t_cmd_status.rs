@@ -0,0 +1,130 @@
+use core::time::Duration;
+
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    frame::{Frame, SlaveAddress},
+    slave::transceiver::{
+        test::{
+            encode, new_transceiver_in_sync, test_rx_no_response, test_state, test_sync, test_tx,
+        },
+        State,
+    },
+    system::WATCHDOG_OFFSET,
+    START_BYTE,
+};
+
+/// Returns a status read of the watchdog timeout of the slaves
+/// with the logical addresses `1` to `3`, using slots of 1ms
+fn status_read() -> Vec<u8> {
+    encode(
+        &Frame::StatusRead {
+            first: [1, 0],
+            slots: 3,
+            slot_time: 1000,
+            offset: WATCHDOG_OFFSET,
+            size: 2,
+        },
+        0,
+    )
+}
+
+/// Create a new transceiver with the `logical_address` that
+/// received [status_read] and waits for the slots
+macro_rules! new_status_slots {
+    ($t: ident, $logical_address: expr) => {
+        new_transceiver_in_sync!($t);
+        $t.logical_address = $logical_address;
+        $t.set_watchdog(Some(Duration::from_millis(0x0201)));
+        $t.set_status_read(true);
+
+        for b in status_read() {
+            test_rx_no_response!($t, b);
+        }
+        test_state!($t, State::StatusSlots);
+    };
+}
+
+#[test]
+fn response_in_slot() {
+    new_status_slots!(t, [2, 0]);
+
+    // The slots start with the first tick after the frame
+    t.tick(Duration::from_millis(10));
+    assert_eq!(t.handle(None), None);
+
+    t.tick(Duration::from_millis(11));
+    test_state!(t, State::StatusResponse);
+
+    let response = [2, 0, 0x01, 0x02];
+    for b in response {
+        test_tx!(t, b);
+    }
+    test_tx!(t, CRC8Autosar::new().update_move(&response).finalize());
+    assert_eq!(t.handle(None), None);
+    test_state!(t, State::StatusSlots);
+
+    // The responses of the other slaves are ignored
+    test_rx_no_response!(t, START_BYTE);
+    t.tick(Duration::from_millis(12));
+    test_state!(t, State::StatusSlots);
+
+    t.tick(Duration::from_millis(13));
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
+
+#[test]
+fn no_slot() {
+    new_status_slots!(t, [4, 0]);
+
+    for now in 10..13 {
+        t.tick(Duration::from_millis(now));
+        assert_eq!(t.handle(None), None);
+        test_rx_no_response!(t, START_BYTE);
+        test_state!(t, State::StatusSlots);
+    }
+
+    t.tick(Duration::from_millis(13));
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
+
+#[test]
+fn collision() {
+    new_status_slots!(t, [1, 0]);
+
+    t.tick(Duration::from_millis(10));
+    test_tx!(t, 1);
+
+    // Another slave transmits in our slot
+    test_rx_no_response!(t, 0);
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, false);
+}
+
+#[test]
+fn not_ticked() {
+    new_transceiver_in_sync!(t);
+    t.logical_address = [1, 0];
+
+    // Status reads are disabled, so we are not waiting for any ticks
+    for b in status_read() {
+        test_rx_no_response!(t, b);
+    }
+    test_state!(t, State::WaitForStart);
+    assert_eq!(t.handle(None), None);
+
+    let frame = Frame::MemWrite {
+        address: SlaveAddress::Broadcast,
+        offset: WATCHDOG_OFFSET,
+        payload: &[0x10, 0x00],
+    };
+    for b in encode(&frame, 1) {
+        test_rx_no_response!(t, b);
+    }
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+    assert_eq!(t.watchdog(), Some(Duration::from_millis(0x10)));
+    assert_eq!(t.diagnostics().valid_frames, 2);
+}
@@ -0,0 +1,197 @@
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    frame::{Frame, SlaveAddress},
+    slave::transceiver::{
+        test::{
+            encode, new_transceiver, new_transceiver_in_sync, test_rx_no_response, test_sync,
+            test_tx, ADDR,
+        },
+        Event, SyncLossReason,
+    },
+    system::{Diagnostics, DIAGNOSTICS_OFFSET, DIAGNOSTICS_SIZE},
+};
+
+#[test]
+fn valid_frames() {
+    new_transceiver_in_sync!(t);
+
+    for seq in 0..3 {
+        for b in encode(&Frame::Nop, seq) {
+            test_rx_no_response!(t, b);
+        }
+    }
+
+    test_sync!(t, true);
+    assert_eq!(
+        t.diagnostics(),
+        &Diagnostics {
+            valid_frames: 3,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn frame_crc_error() {
+    new_transceiver_in_sync!(t);
+
+    let mut data = encode(&Frame::Nop, 0);
+    *data.last_mut().unwrap() ^= 0xFF;
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    test_sync!(t, false);
+    assert_eq!(t.diagnostics().frame_crc_errors, 1);
+    assert_eq!(t.diagnostics().sync_lost, 1);
+    assert_eq!(t.diagnostics().valid_frames, 0);
+}
+
+#[test]
+fn header_crc_error() {
+    new_transceiver_in_sync!(t, ADDR);
+
+    let frame = Frame::MemRead {
+        address: SlaveAddress::Physical(ADDR),
+        offset: 0,
+        size: 1,
+        payload: &[],
+    };
+    let mut data = encode(&frame, 0);
+    *data.last_mut().unwrap() ^= 0xFF;
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    test_sync!(t, false);
+    assert_eq!(t.diagnostics().header_crc_errors, 1);
+    assert_eq!(t.diagnostics().sync_lost, 1);
+}
+
+#[test]
+fn sequence_error() {
+    new_transceiver_in_sync!(t);
+
+    for b in encode(&Frame::Nop, 2) {
+        test_rx_no_response!(t, b);
+    }
+
+    test_sync!(t, false);
+    assert_eq!(t.diagnostics().sequence_errors, 1);
+    assert_eq!(t.diagnostics().sync_lost, 1);
+}
+
+#[test]
+fn unknown_command() {
+    new_transceiver_in_sync!(t);
+
+    test_rx_no_response!(t, 0x55);
+    test_rx_no_response!(t, 0x1F);
+
+    test_sync!(t, false);
+    assert_eq!(t.diagnostics().unknown_commands, 1);
+    assert_eq!(t.diagnostics().sync_lost, 1);
+}
+
+#[test]
+fn sync_gained() {
+    new_transceiver_in_sync!(t);
+    t.in_sync = false;
+
+    for b in encode(&Frame::Sync { version: 1 }, 0) {
+        test_rx_no_response!(t, b);
+    }
+
+    test_sync!(t, true);
+    assert_eq!(t.diagnostics().sync_gained, 1);
+    assert_eq!(t.diagnostics().valid_frames, 1);
+
+    t.clear_diagnostics();
+    assert_eq!(t.diagnostics(), &Diagnostics::default());
+}
+
+#[test]
+fn read_diagnostics() {
+    new_transceiver_in_sync!(t, ADDR);
+
+    for b in encode(&Frame::Nop, 0) {
+        test_rx_no_response!(t, b);
+    }
+
+    // The scratchpad cannot hold the complete block, so read the first counters only
+    let frame = Frame::MemRead {
+        address: SlaveAddress::Physical(ADDR),
+        offset: DIAGNOSTICS_OFFSET,
+        size: 8,
+        payload: &[],
+    };
+    let mut data = encode(&frame, 1);
+
+    let mut crc = CRC8Autosar::new();
+    crc.update(&data);
+    let header_crc = data.pop().unwrap();
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    let bytes = t.diagnostics().to_bytes();
+    assert_eq!(bytes[..4], 1u32.to_le_bytes());
+
+    test_tx!(t, header_crc, bytes[0]);
+    for b in &bytes[1..8] {
+        test_tx!(t, *b);
+    }
+    crc.update(&bytes[..8]);
+    test_tx!(t, crc.finalize());
+
+    test_sync!(t, true);
+    assert_eq!(t.diagnostics().valid_frames, 2);
+    assert_eq!(
+        Diagnostics::parse(&bytes),
+        Some(Diagnostics {
+            valid_frames: 1,
+            ..Default::default()
+        })
+    );
+    assert!(Diagnostics::parse(&bytes[..DIAGNOSTICS_SIZE - 1]).is_none());
+}
+
+#[test]
+fn diagnostics_read_only() {
+    new_transceiver_in_sync!(t, ADDR);
+
+    let frame = Frame::MemWrite {
+        address: SlaveAddress::Physical(ADDR),
+        offset: DIAGNOSTICS_OFFSET,
+        payload: &[0u8; 4],
+    };
+    for b in encode(&frame, 0) {
+        test_rx_no_response!(t, b);
+    }
+
+    // The rejected write does not count as a valid frame
+    test_sync!(t, false);
+    assert_eq!(t.diagnostics().memory_errors, 1);
+    assert_eq!(t.diagnostics().valid_frames, 0);
+}
+
+#[test]
+fn scratchpad_overflow() {
+    new_transceiver!(t, [0; 8], in_sync);
+
+    let frame = Frame::MemWrite {
+        address: SlaveAddress::Physical(ADDR),
+        offset: 0,
+        payload: &[0u8; 0x20],
+    };
+    for b in encode(&frame, 0) {
+        test_rx_no_response!(t, b);
+    }
+
+    test_sync!(t, false);
+    assert_eq!(t.diagnostics().memory_errors, 1);
+    assert_eq!(
+        t.memory().events,
+        vec![Event::SyncLost(SyncLossReason::ScratchpadOverflow)]
+    );
+}
@@ -0,0 +1,195 @@
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    frame::{Frame, SlaveAddress},
+    slave::transceiver::{
+        test::{encode, new_transceiver, test_state, test_sync, ADDR},
+        State,
+    },
+};
+
+/// Returns a read at `offset` from `address` with
+/// the `payload` in place of the data
+fn read(address: [u8; 6], offset: u16, payload: &[u8]) -> Vec<u8> {
+    let frame = Frame::MemRead {
+        address: SlaveAddress::Physical(address),
+        offset,
+        size: payload.len() as u16,
+        payload: &[],
+    };
+    let mut data = encode(&frame, 0);
+
+    data.extend_from_slice(payload);
+    data.push(CRC8Autosar::new().update_move(&data).finalize());
+    data
+}
+
+#[test]
+fn read_fills_placeholders() {
+    new_transceiver!(t, [0, 1, 2, 3, 4, 5, 6, 7], in_sync);
+
+    let frame = read(ADDR, 2, &[0, 0, 0]);
+    let forwarded: Vec<u8> = frame.iter().map(|b| t.forward(*b)).collect();
+    assert_eq!(forwarded, read(ADDR, 2, &[2, 3, 4]));
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+    assert_eq!(t.diagnostics().valid_frames, 1);
+}
+
+#[test]
+fn read_of_other_slave() {
+    new_transceiver!(t, [0, 1, 2, 3, 4, 5, 6, 7], in_sync);
+
+    let frame = read([0xFF; 6], 2, &[0xA, 0xB]);
+    let forwarded: Vec<u8> = frame.iter().map(|b| t.forward(*b)).collect();
+    assert_eq!(forwarded, frame);
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
+
+#[test]
+fn write() {
+    new_transceiver!(t, [0, 1, 2, 3, 4, 5, 6, 7], in_sync);
+
+    let frame = Frame::MemWrite {
+        address: SlaveAddress::Physical(ADDR),
+        offset: 6,
+        payload: &[0xA, 0xB],
+    };
+    let data = encode(&frame, 0);
+
+    let forwarded: Vec<u8> = data.iter().map(|b| t.forward(*b)).collect();
+    assert_eq!(forwarded, data);
+    assert_eq!(t.memory().data, [0, 1, 2, 3, 4, 5, 0xA, 0xB]);
+}
+
+#[test]
+fn corrupted_crc() {
+    new_transceiver!(t, [0, 1, 2, 3, 4, 5, 6, 7], in_sync);
+
+    let mut frame = read(ADDR, 0, &[0, 0]);
+    *frame.last_mut().unwrap() ^= 0x01;
+    let forwarded: Vec<u8> = frame.iter().map(|b| t.forward(*b)).collect();
+
+    // The mismatch of the CRC is kept for the nodes further downstream
+    let mut expected = read(ADDR, 0, &[0, 1]);
+    *expected.last_mut().unwrap() ^= 0x01;
+    assert_eq!(forwarded, expected);
+
+    test_sync!(t, false);
+    assert_eq!(t.diagnostics().frame_crc_errors, 1);
+}
+
+/// Returns a position addressed read of `size` bytes at `offset`
+fn position_read(position: u16, offset: u16, size: u16) -> Vec<u8> {
+    encode(
+        &Frame::MemRead {
+            address: SlaveAddress::Position(position),
+            offset,
+            size,
+            payload: &[],
+        },
+        0,
+    )
+}
+
+#[test]
+fn position_decrement() {
+    new_transceiver!(t, [0, 1, 2, 3, 4, 5, 6, 7], in_sync);
+
+    let mut frame = position_read(0x0100, 0, 2);
+    frame.extend_from_slice(&[0xA, 0xB]);
+    frame.push(CRC8Autosar::new().update_move(&frame).finalize());
+
+    // The position is passed on decremented along with
+    // updated CRCs, the placeholders are kept
+    let mut expected = position_read(0x00FF, 0, 2);
+    expected.extend_from_slice(&[0xA, 0xB]);
+    expected.push(CRC8Autosar::new().update_move(&expected).finalize());
+
+    let forwarded: Vec<u8> = frame.iter().map(|b| t.forward(*b)).collect();
+    assert_eq!(forwarded, expected);
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
+
+#[test]
+fn position_targeted() {
+    new_transceiver!(t, [0, 1, 2, 3, 4, 5, 6, 7], in_sync);
+
+    let mut frame = position_read(0, 4, 2);
+    frame.extend_from_slice(&[0, 0]);
+    frame.push(CRC8Autosar::new().update_move(&frame).finalize());
+
+    let mut expected = position_read(0xFFFF, 4, 2);
+    expected.extend_from_slice(&[4, 5]);
+    expected.push(CRC8Autosar::new().update_move(&expected).finalize());
+
+    let forwarded: Vec<u8> = frame.iter().map(|b| t.forward(*b)).collect();
+    assert_eq!(forwarded, expected);
+
+    test_sync!(t, true);
+    assert_eq!(t.diagnostics().valid_frames, 1);
+}
+
+/// Returns a broadcast read at `offset` with the `payload` in place of the data
+fn broadcast_read(offset: u16, payload: &[u8]) -> Vec<u8> {
+    let frame = Frame::MemRead {
+        address: SlaveAddress::Broadcast,
+        offset,
+        size: payload.len() as u16,
+        payload: &[],
+    };
+    let mut data = encode(&frame, 0);
+
+    data.extend_from_slice(payload);
+    data.push(CRC8Autosar::new().update_move(&data).finalize());
+    data
+}
+
+#[test]
+fn broadcast_read_combined() {
+    new_transceiver!(t, [0, 1, 2, 3, 4, 5, 6, 7], in_sync);
+
+    // The data of the slaves upstream is combined with ours
+    let frame = broadcast_read(2, &[0x10, 0x20, 0x40]);
+    let forwarded: Vec<u8> = frame.iter().map(|b| t.forward(*b)).collect();
+    assert_eq!(forwarded, broadcast_read(2, &[0x12, 0x23, 0x44]));
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
+
+#[test]
+fn broadcast_read_shared_medium() {
+    new_transceiver!(t, [0, 1, 2, 3, 4, 5, 6, 7], in_sync);
+
+    // The responses of multiple slaves would collide, so none is sent
+    let frame = broadcast_read(2, &[]);
+    for b in &frame[..frame.len() - 1] {
+        assert_eq!(t.handle(Some(*b)), None);
+    }
+    assert_eq!(t.handle(None), None);
+}
+
+#[test]
+fn position_shared_medium() {
+    new_transceiver!(t, [0, 1, 2, 3, 4, 5, 6, 7], in_sync);
+
+    let frame = Frame::MemWrite {
+        address: SlaveAddress::Position(0),
+        offset: 0,
+        payload: &[0xA, 0xB],
+    };
+
+    // All slaves on a shared medium would take the position for theirs
+    for b in encode(&frame, 0) {
+        assert_eq!(t.handle(Some(b)), None);
+    }
+    assert_eq!(t.memory().data, [0, 1, 2, 3, 4, 5, 6, 7]);
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
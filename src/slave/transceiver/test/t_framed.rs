@@ -0,0 +1,166 @@
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    frame::{Frame, SlaveAddress},
+    slave::transceiver::{
+        test::{encode, new_transceiver, test_state, test_sync, ADDR},
+        FrameError, State,
+    },
+};
+
+fn read(seq: u8, offset: u16, size: u16) -> Vec<u8> {
+    encode(
+        &Frame::MemRead {
+            address: SlaveAddress::Physical(ADDR),
+            offset,
+            size,
+            payload: &[],
+        },
+        seq,
+    )
+}
+
+/// Returns the expected response to the `request` with `payload`
+fn response(request: &[u8], payload: &[u8]) -> Vec<u8> {
+    let crc = CRC8Autosar::new()
+        .update_move(request)
+        .update_move(payload)
+        .finalize();
+    payload.iter().copied().chain([crc]).collect()
+}
+
+#[test]
+fn without_sync() {
+    new_transceiver!(t, [0; 8]);
+    let mut buf = vec![0u8; t.max_response_len()];
+
+    let write = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::Physical(ADDR),
+            offset: 1,
+            payload: &[1, 2, 3],
+        },
+        2,
+    );
+    assert_eq!(t.handle_frame(&write, &mut buf), Ok(0));
+    test_sync!(t, true);
+    assert_eq!(t.memory().data, [0, 1, 2, 3, 0, 0, 0, 0]);
+
+    let request = read(3, 0, 4);
+    let len = t.handle_frame(&request, &mut buf).unwrap();
+    assert_eq!(&buf[..len], &response(&request, &[0, 1, 2, 3]));
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+    assert_eq!(t.diagnostics().valid_frames, 2);
+    assert_eq!(t.diagnostics().sync_gained, 1);
+}
+
+#[test]
+fn corrupted_without_sync() {
+    new_transceiver!(t, [0; 8]);
+    let mut buf = vec![0u8; t.max_response_len()];
+
+    let mut write = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::Physical(ADDR),
+            offset: 1,
+            payload: &[1, 2, 3],
+        },
+        2,
+    );
+    *write.last_mut().unwrap() ^= 0x01;
+
+    // The sync is only gained once the CRC has been validated
+    assert_eq!(t.handle_frame(&write, &mut buf), Ok(0));
+    test_sync!(t, false);
+    assert_eq!(t.memory().data, [0; 8]);
+    assert_eq!(t.diagnostics().frame_crc_errors, 1);
+    assert_eq!(t.diagnostics().sync_gained, 0);
+    assert_eq!(t.diagnostics().sync_lost, 0);
+
+    // Frames ending before any CRC are dropped just as well
+    assert_eq!(t.handle_frame(&write[..4], &mut buf), Ok(0));
+    test_sync!(t, false);
+    assert_eq!(t.diagnostics().sync_gained, 0);
+}
+
+#[test]
+fn sequence_error() {
+    new_transceiver!(t, [0; 8]);
+    let mut buf = vec![0u8; t.max_response_len()];
+
+    assert_eq!(t.handle_frame(&encode(&Frame::Nop, 0), &mut buf), Ok(0));
+
+    // A frame got lost, so this one is dropped
+    assert_eq!(t.handle_frame(&read(2, 0, 1), &mut buf), Ok(0));
+    test_sync!(t, false);
+    assert_eq!(t.diagnostics().sequence_errors, 1);
+
+    // The next frame is accepted again
+    let request = read(3, 0, 1);
+    let len = t.handle_frame(&request, &mut buf).unwrap();
+    assert_eq!(&buf[..len], &response(&request, &[0]));
+    test_sync!(t, true);
+}
+
+#[test]
+fn truncated() {
+    new_transceiver!(t, [0; 8]);
+    let mut buf = vec![0u8; t.max_response_len()];
+
+    let write = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::Physical(ADDR),
+            offset: 0,
+            payload: &[1, 2, 3],
+        },
+        0,
+    );
+    assert_eq!(t.handle_frame(&write[..write.len() - 2], &mut buf), Ok(0));
+    test_state!(t, State::WaitForStart);
+    assert_eq!(t.memory().data, [0u8; 8]);
+
+    let request = read(1, 0, 2);
+    let len = t.handle_frame(&request, &mut buf).unwrap();
+    assert_eq!(&buf[..len], &response(&request, &[0, 0]));
+}
+
+#[test]
+fn response_buffer_too_small() {
+    new_transceiver!(t, [0; 8]);
+    let mut buf = vec![0u8; t.max_response_len() - 1];
+
+    let write = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::Physical(ADDR),
+            offset: 0,
+            payload: &[1, 2, 3],
+        },
+        0,
+    );
+
+    // The frame is rejected before it has any effect
+    assert_eq!(
+        t.handle_frame(&write, &mut buf),
+        Err(FrameError::ResponseBufferTooSmall)
+    );
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, false);
+    assert_eq!(t.memory().data, [0; 8]);
+    assert!(t.memory().events.is_empty());
+    assert_eq!(t.diagnostics().valid_frames, 0);
+}
+
+#[test]
+fn largest_response() {
+    new_transceiver!(t, [0; 8], in_sync);
+    t.system.ack_slots = 4;
+    let mut buf = vec![0u8; t.max_response_len()];
+
+    // The whole scratchpad is read, followed by the CRC and the ack slots
+    let request = read(0, 0xFF00, 0xf);
+    let len = t.handle_frame(&request, &mut buf).unwrap();
+    assert_eq!(len, 0xf + 1 + 4);
+    assert_eq!(buf[..len - 4], response(&request, &buf[..0xf]));
+    assert_eq!(t.diagnostics().valid_frames, 1);
+}
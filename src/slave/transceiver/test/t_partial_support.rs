@@ -0,0 +1,197 @@
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    frame::{Frame, SlaveAddress},
+    slave::transceiver::{
+        test::{
+            empty_callback, encode, new_transceiver_in_sync, test_rx_no_response, test_state,
+            test_sync, ADDR,
+        },
+        State, Transceiver,
+    },
+    system::MmuEntry,
+};
+
+const OTHER: [u8; 6] = [6, 5, 4, 3, 2, 1];
+
+#[test]
+fn oversized_write_not_targeted() {
+    new_transceiver_in_sync!(t, ADDR);
+
+    let data = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::Physical(OTHER),
+            offset: 0,
+            payload: &[0xAA; 0x200],
+        },
+        0,
+    );
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
+
+#[test]
+fn oversized_write_targeted() {
+    new_transceiver_in_sync!(t, ADDR);
+
+    let data = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::Physical(ADDR),
+            offset: 0,
+            payload: &[0xAA; 0x10],
+        },
+        0,
+    );
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    test_sync!(t, false);
+}
+
+#[test]
+fn oversized_read_not_targeted() {
+    new_transceiver_in_sync!(t, ADDR);
+
+    let payload = [0x55; 0x100];
+    let data = encode(
+        &Frame::MemRead {
+            address: SlaveAddress::Physical(OTHER),
+            offset: 0,
+            size: payload.len() as u16,
+            payload: &[],
+        },
+        0,
+    );
+    let crc = CRC8Autosar::new()
+        .update_move(&data)
+        .update_move(&payload)
+        .finalize();
+
+    for b in data.into_iter().chain(payload).chain([crc]) {
+        test_rx_no_response!(t, b);
+    }
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
+
+#[test]
+fn oversized_read_targeted() {
+    new_transceiver_in_sync!(t, ADDR);
+
+    let data = encode(
+        &Frame::MemRead {
+            address: SlaveAddress::Physical(ADDR),
+            offset: 0,
+            size: 0x10,
+            payload: &[],
+        },
+        0,
+    );
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, false);
+}
+
+#[test]
+fn oversized_logical_targeted() {
+    new_transceiver_in_sync!(t, ADDR);
+    t.set_mmu_entry(
+        0,
+        Some(MmuEntry {
+            logical_start: 0,
+            length: 0x10,
+            physical_start: 0,
+            read: true,
+            write: true,
+        }),
+    );
+
+    let data = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::LogicalMemory,
+            offset: 0,
+            payload: &[0xAA; 0x20],
+        },
+        0,
+    );
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    test_sync!(t, false);
+}
+
+#[test]
+fn logical_read_write_only_window() {
+    let mut scratchpad = [0u8; 4];
+    let mut t = Transceiver::new(&mut scratchpad, ADDR, empty_callback);
+    t.in_sync = true;
+    t.sequence_no = 0b11;
+    t.set_mmu_entry(
+        0,
+        Some(MmuEntry {
+            logical_start: 0,
+            length: 100,
+            physical_start: 0,
+            read: false,
+            write: true,
+        }),
+    );
+
+    // The payload of the read is sent by other slaves and never buffered
+    let data = encode(
+        &Frame::MemRead {
+            address: SlaveAddress::LogicalMemory,
+            offset: 0,
+            size: 100,
+            payload: &[0x55; 100],
+        },
+        0,
+    );
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
+
+#[test]
+fn oversized_logical_read_targeted() {
+    new_transceiver_in_sync!(t, ADDR);
+    t.set_mmu_entry(
+        0,
+        Some(MmuEntry {
+            logical_start: 0,
+            length: 0x10,
+            physical_start: 0,
+            read: true,
+            write: false,
+        }),
+    );
+
+    // The window does not fit into the scratchpad, so we drop out at the header CRC
+    let data = encode(
+        &Frame::MemRead {
+            address: SlaveAddress::LogicalMemory,
+            offset: 0,
+            size: 0x20,
+            payload: &[],
+        },
+        0,
+    );
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, false);
+}
@@ -0,0 +1,120 @@
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    frame::{Frame, SlaveAddress},
+    slave::transceiver::{
+        test::{
+            encode, new_transceiver, test_rx_no_response, test_state, test_sync, test_tx, ADDR,
+        },
+        Event, State, SyncLossReason,
+    },
+    CMD_RESET, CMD_SYNC, PROTOCOL_VERSION_1, START_BYTE, SYNC_SEQUENCE,
+};
+
+#[test]
+fn read_write() {
+    new_transceiver!(t, [0; 8], in_sync);
+
+    let data = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::Physical(ADDR),
+            offset: 2,
+            payload: &[1, 2, 3],
+        },
+        0,
+    );
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+    assert_eq!(t.memory().data, [0, 0, 1, 2, 3, 0, 0, 0]);
+
+    let mut data = encode(
+        &Frame::MemRead {
+            address: SlaveAddress::Physical(ADDR),
+            offset: 3,
+            size: 2,
+            payload: &[],
+        },
+        1,
+    );
+    let crc = CRC8Autosar::new()
+        .update_move(&data)
+        .update_move(&[2, 3])
+        .finalize();
+    let header_crc = data.pop().unwrap();
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+    test_tx!(t, header_crc, 2);
+    test_tx!(t, 3);
+    test_tx!(t, crc);
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+    assert_eq!(
+        t.memory().events,
+        vec![
+            Event::WriteCommitted { offset: 2, len: 3 },
+            Event::ReadServed { offset: 3, len: 2 }
+        ]
+    );
+}
+
+#[test]
+fn memory_error() {
+    new_transceiver!(t, [0; 8], in_sync);
+
+    // The memory is only 8 bytes large
+    let data = encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::Physical(ADDR),
+            offset: 6,
+            payload: &[1, 2, 3],
+        },
+        0,
+    );
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    test_sync!(t, false);
+    assert_eq!(t.memory().data, [0u8; 8]);
+    assert_eq!(
+        t.memory().events,
+        vec![Event::SyncLost(SyncLossReason::CallbackError)]
+    );
+}
+
+#[test]
+fn hooks() {
+    new_transceiver!(t, [0xAA; 8]);
+
+    let mut data = vec![START_BYTE, CMD_SYNC];
+    data.extend_from_slice(&SYNC_SEQUENCE);
+    data.push(PROTOCOL_VERSION_1);
+    data.push(CRC8Autosar::new().update_move(&data).finalize());
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+    test_sync!(t, true);
+
+    let data = [START_BYTE, CMD_RESET | 1 << 6];
+    let crc = CRC8Autosar::new().update_move(&data).finalize();
+    for b in data.into_iter().chain([crc]) {
+        test_rx_no_response!(t, b);
+    }
+
+    // Only actual changes of the sync state are reported
+    t.loose_sync(SyncLossReason::UnexpectedRx);
+    t.loose_sync(SyncLossReason::UnexpectedRx);
+
+    let memory = t.memory();
+    assert_eq!(memory.resets, 1);
+    assert_eq!(memory.data, [0u8; 8]);
+    assert_eq!(
+        memory.events,
+        vec![
+            Event::SyncGained,
+            Event::SyncLost(SyncLossReason::UnexpectedRx)
+        ]
+    );
+}
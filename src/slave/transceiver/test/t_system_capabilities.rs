@@ -0,0 +1,98 @@
+use crate::{
+    crc8::{CRC8Autosar, CRC},
+    frame::{Frame, SlaveAddress},
+    slave::transceiver::{
+        test::{new_transceiver_in_sync, test_rx_no_response, test_state, test_sync, test_tx},
+        State,
+    },
+    system::{
+        FeatureSupport, SlaveCapabilities, CAPABILITIES_OFFSET, CAPABILITIES_SIZE, MMU_COUNT,
+    },
+};
+
+#[test]
+fn read_capabilities() {
+    let addr = [1, 2, 3, 4, 5, 6];
+    new_transceiver_in_sync!(t, addr);
+
+    let frame = Frame::MemRead {
+        address: SlaveAddress::Physical(addr),
+        offset: CAPABILITIES_OFFSET,
+        size: CAPABILITIES_SIZE as u16,
+        payload: &[],
+    };
+    let mut data = vec![0u8; frame.encoded_len().unwrap()];
+    frame.encode(0, &mut data).unwrap();
+
+    let mut crc = CRC8Autosar::new();
+    crc.update(&data);
+    let header_crc = data.pop().unwrap();
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    let expected = SlaveCapabilities {
+        protocol_versions: 0b1,
+        scratchpad_size: 0xf,
+        long_offset: FeatureSupport::Full,
+        long_size: FeatureSupport::Partial,
+        logical_addressing: FeatureSupport::Full,
+        mmu: FeatureSupport::Full,
+        mmu_count: MMU_COUNT as u8,
+    };
+    assert_eq!(t.capabilities(), expected);
+
+    let bytes = expected.to_bytes();
+    test_tx!(t, header_crc, bytes[0]);
+    for b in &bytes[1..] {
+        test_tx!(t, *b);
+    }
+    crc.update(&bytes);
+    test_tx!(t, crc.finalize());
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+    assert_eq!(SlaveCapabilities::parse(&bytes), Some(expected));
+}
+
+#[test]
+fn capabilities_read_only() {
+    let addr = [1, 2, 3, 4, 5, 6];
+    new_transceiver_in_sync!(t, addr);
+
+    let frame = Frame::MemWrite {
+        address: SlaveAddress::Physical(addr),
+        offset: CAPABILITIES_OFFSET,
+        payload: &[0u8; CAPABILITIES_SIZE],
+    };
+    let mut data = vec![0u8; frame.encoded_len().unwrap()];
+    frame.encode(0, &mut data).unwrap();
+
+    for b in data {
+        test_rx_no_response!(t, b);
+    }
+
+    test_sync!(t, false);
+}
+
+#[test]
+fn parse_invalid() {
+    let mut bytes = SlaveCapabilities {
+        protocol_versions: 0b1,
+        scratchpad_size: 0x100,
+        long_offset: FeatureSupport::Full,
+        long_size: FeatureSupport::Full,
+        logical_addressing: FeatureSupport::None,
+        mmu: FeatureSupport::None,
+        mmu_count: 0,
+    }
+    .to_bytes();
+    assert!(SlaveCapabilities::parse(&bytes[..CAPABILITIES_SIZE - 1]).is_none());
+
+    bytes[4] = 0b11;
+    assert!(SlaveCapabilities::parse(&bytes).is_none());
+
+    bytes[4] = 0;
+    bytes[0] = 0xFF;
+    assert!(SlaveCapabilities::parse(&bytes).is_none());
+}
@@ -0,0 +1,103 @@
+use crate::{
+    frame::{Frame, SlaveAddress},
+    slave::transceiver::{
+        test::{
+            encode, ms, new_transceiver_in_sync, test_rx_no_response, test_state, test_sync, ADDR,
+        },
+        State, Timeouts,
+    },
+    SYNC_SEQUENCE,
+};
+
+#[test]
+fn disabled() {
+    new_transceiver_in_sync!(t);
+
+    test_rx_no_response!(t, 0x55);
+    assert!(!t.tick(ms(0)));
+    assert!(!t.tick(ms(1000)));
+    test_state!(t, State::WaitForCommand);
+}
+
+#[test]
+fn inter_byte() {
+    new_transceiver_in_sync!(t, ADDR);
+    t.set_timeouts(Timeouts {
+        inter_byte: Some(ms(2)),
+        frame: None,
+        ack_slot: None,
+    });
+
+    let frame = Frame::MemWrite {
+        address: SlaveAddress::Physical(ADDR),
+        offset: 0,
+        payload: &[1, 2, 3],
+    };
+    let data = encode(&frame, 0);
+
+    // The frame is cut off in the middle of the payload
+    for (i, b) in data[..data.len() - 2].iter().enumerate() {
+        test_rx_no_response!(t, *b);
+        assert!(!t.tick(ms(i as u64)));
+    }
+    test_state!(t, State::MEMRxPayload);
+
+    let last = data.len() as u64 - 3;
+    assert!(!t.tick(ms(last + 2)));
+    assert!(t.tick(ms(last + 3)));
+    test_state!(t, State::WaitForStart);
+    assert!(!t.tick(ms(last + 4)));
+
+    // The next frame is received completely
+    for b in encode(&frame, 1) {
+        test_rx_no_response!(t, b);
+    }
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+    assert_eq!(t.diagnostics().valid_frames, 1);
+}
+
+#[test]
+fn frame() {
+    new_transceiver_in_sync!(t);
+    t.set_timeouts(Timeouts {
+        inter_byte: Some(ms(2)),
+        frame: Some(ms(5)),
+        ack_slot: None,
+    });
+
+    test_rx_no_response!(t, 0x55);
+    assert!(!t.tick(ms(10)));
+    test_rx_no_response!(t, 0x01);
+    test_state!(t, State::Sync);
+
+    // The bytes keep coming, but the frame takes too long
+    for (time, b) in (11..=15).zip(SYNC_SEQUENCE) {
+        test_rx_no_response!(t, b);
+        assert!(!t.tick(ms(time)));
+    }
+    test_rx_no_response!(t, SYNC_SEQUENCE[5]);
+    assert!(t.tick(ms(16)));
+    test_state!(t, State::WaitForStart);
+}
+
+#[test]
+fn frame_restarts() {
+    new_transceiver_in_sync!(t);
+    t.set_timeouts(Timeouts {
+        inter_byte: None,
+        frame: Some(ms(5)),
+        ack_slot: None,
+    });
+
+    // A complete frame between two ticks does not count towards the next one
+    test_rx_no_response!(t, 0x55);
+    assert!(!t.tick(ms(0)));
+    for b in &encode(&Frame::Nop, 0)[1..] {
+        test_rx_no_response!(t, *b);
+    }
+    test_rx_no_response!(t, 0x55);
+    assert!(!t.tick(ms(4)));
+    assert!(!t.tick(ms(9)));
+    assert!(t.tick(ms(10)));
+}
@@ -0,0 +1,116 @@
+use core::time::Duration;
+
+use crate::{
+    frame::{Frame, SlaveAddress},
+    slave::transceiver::{
+        test::{encode, ms, new_transceiver, test_rx_no_response, test_state, test_sync, ADDR},
+        Event, State, SyncLossReason,
+    },
+    system::WATCHDOG_OFFSET,
+};
+
+#[test]
+fn expires() {
+    new_transceiver!(t, [0; 8], in_sync);
+    t.set_watchdog(Some(ms(10)));
+    t.tick(ms(0));
+
+    // Valid frames feed the watchdog
+    for (seq, time) in [(0, 8), (1, 16)] {
+        for b in encode(&Frame::Nop, seq) {
+            test_rx_no_response!(t, b);
+        }
+        t.tick(ms(time));
+    }
+    t.tick(ms(26));
+    test_sync!(t, true);
+    assert!(t.memory().events.is_empty());
+
+    t.tick(ms(27));
+    test_sync!(t, false);
+    assert_eq!(
+        t.memory().events,
+        vec![
+            Event::WatchdogExpired,
+            Event::SyncLost(SyncLossReason::Watchdog)
+        ]
+    );
+
+    // The watchdog is not armed while out of sync
+    t.tick(ms(100));
+    assert_eq!(t.memory().events.len(), 2);
+    assert_eq!(t.diagnostics().sync_lost, 1);
+}
+
+#[test]
+fn disabled() {
+    new_transceiver!(t, [0; 8], in_sync);
+    assert_eq!(t.watchdog(), None);
+
+    t.tick(ms(0));
+    t.tick(ms(100_000));
+    test_sync!(t, true);
+    assert!(t.memory().events.is_empty());
+}
+
+#[test]
+fn configure() {
+    new_transceiver!(t, [0; 8], in_sync);
+
+    let frame = Frame::MemWrite {
+        address: SlaveAddress::Physical(ADDR),
+        offset: WATCHDOG_OFFSET,
+        payload: &500u16.to_le_bytes(),
+    };
+    for b in encode(&frame, 0) {
+        test_rx_no_response!(t, b);
+    }
+    test_sync!(t, true);
+    assert_eq!(t.watchdog(), Some(ms(500)));
+
+    // The watchdog is disabled by a reset
+    for b in encode(&Frame::Reset, 1) {
+        test_rx_no_response!(t, b);
+    }
+    assert_eq!(t.watchdog(), None);
+
+    t.set_watchdog(Some(Duration::from_micros(10)));
+    assert_eq!(t.watchdog(), Some(ms(1)));
+    t.set_watchdog(Some(Duration::from_secs(100)));
+    assert_eq!(t.watchdog(), Some(ms(u16::MAX as u64)));
+}
+
+#[test]
+fn expires_during_frame() {
+    new_transceiver!(t, [0; 8], in_sync);
+    t.set_watchdog(Some(ms(10)));
+    t.tick(ms(0));
+
+    let frame = Frame::MemWrite {
+        address: SlaveAddress::Physical(ADDR),
+        offset: 0,
+        payload: &[1, 2, 3, 4],
+    };
+    let data = encode(&frame, 0);
+    let (first, last) = data.split_at(data.len() - 3);
+    for b in first {
+        test_rx_no_response!(t, *b);
+    }
+
+    // The rest of the frame is not committed after the safe state
+    t.tick(ms(11));
+    test_state!(t, State::WaitForStart);
+    for b in last {
+        test_rx_no_response!(t, *b);
+    }
+
+    test_sync!(t, false);
+    assert_eq!(t.memory().data, [0; 8]);
+    assert_eq!(
+        t.memory().events,
+        vec![
+            Event::WatchdogExpired,
+            Event::SyncLost(SyncLossReason::Watchdog)
+        ]
+    );
+}
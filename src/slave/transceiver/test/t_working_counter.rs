@@ -0,0 +1,131 @@
+use crate::{
+    frame::{Frame, SlaveAddress},
+    slave::transceiver::{
+        state::State,
+        test::{encode, ms, new_transceiver_in_sync, test_state, test_sync, ADDR},
+        Timeouts,
+    },
+    START_BYTE,
+};
+
+fn write(address: [u8; 6]) -> Vec<u8> {
+    encode(
+        &Frame::MemWrite {
+            address: SlaveAddress::Physical(address),
+            offset: 0,
+            payload: &[1, 2],
+        },
+        0,
+    )
+}
+
+#[test]
+fn acknowledge_in_slot() {
+    new_transceiver_in_sync!(t, ADDR);
+    t.system.ack_slots = 3;
+    t.system.ack_slot = 1;
+
+    for b in write(ADDR) {
+        assert_eq!(t.handle(Some(b)), None);
+    }
+    test_state!(t, State::MEMAckSlots);
+
+    // We wait for the slot before ours
+    assert_eq!(t.handle(None), None);
+    assert_eq!(t.handle(Some(0)), None);
+    assert_eq!(t.handle(None), Some(1));
+    assert_eq!(t.handle(None), None);
+    assert_eq!(t.handle(Some(1)), None);
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
+
+#[test]
+fn not_targeted() {
+    new_transceiver_in_sync!(t, ADDR);
+    t.system.ack_slots = 1;
+
+    for b in write([0xFF; 6]) {
+        assert_eq!(t.handle(Some(b)), None);
+    }
+    assert_eq!(t.handle(None), Some(0));
+    test_state!(t, State::WaitForStart);
+}
+
+#[test]
+fn management_command() {
+    new_transceiver_in_sync!(t, ADDR);
+    t.system.ack_slots = 1;
+
+    for b in encode(&Frame::Nop, 0) {
+        assert_eq!(t.handle(Some(b)), None);
+    }
+    test_state!(t, State::WaitForStart);
+    assert_eq!(t.handle(None), None);
+}
+
+#[test]
+fn empty_slots() {
+    new_transceiver_in_sync!(t, ADDR);
+    t.system.ack_slots = 2;
+    t.system.ack_slot = 1;
+
+    for b in write(ADDR) {
+        assert_eq!(t.handle(Some(b)), None);
+    }
+
+    // The slot before ours stays empty and the master starts over
+    assert_eq!(t.handle(None), None);
+    assert_eq!(t.handle(Some(START_BYTE)), None);
+    test_state!(t, State::WaitForCommand);
+    test_sync!(t, true);
+}
+
+#[test]
+fn empty_slots_timeout() {
+    new_transceiver_in_sync!(t, ADDR);
+    t.set_timeouts(Timeouts {
+        ack_slot: Some(ms(2)),
+        ..Default::default()
+    });
+    t.system.ack_slots = 4;
+    t.system.ack_slot = 2;
+
+    for b in write(ADDR) {
+        assert_eq!(t.handle(Some(b)), None);
+    }
+    assert!(!t.tick(ms(0)));
+
+    // The slots before ours stay empty, so we skip them
+    assert!(!t.tick(ms(2)));
+    assert_eq!(t.handle(None), None);
+    assert!(!t.tick(ms(3)));
+    assert!(!t.tick(ms(6)));
+
+    // Our own slot is never skipped
+    assert!(!t.tick(ms(10)));
+    assert_eq!(t.handle(None), Some(1));
+
+    // The slot after ours ends the frame once it has been skipped
+    assert!(!t.tick(ms(20)));
+    test_state!(t, State::MEMAckSlots);
+    assert!(!t.tick(ms(23)));
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
+
+#[test]
+fn framed() {
+    new_transceiver_in_sync!(t, ADDR);
+    t.system.ack_slots = 3;
+    t.system.ack_slot = 1;
+
+    // The slots of the other slaves are transmitted empty
+    let mut buf = vec![0u8; t.max_response_len()];
+    assert_eq!(t.handle_frame(&write(ADDR), &mut buf), Ok(3));
+    assert_eq!(buf[..3], [0, 1, 0]);
+
+    test_state!(t, State::WaitForStart);
+    test_sync!(t, true);
+}
@@ -0,0 +1,87 @@
+//! Hosts a slave on a UDP socket, carrying one frame per datagram.
+//!
+//! As datagrams preserve the frame boundaries, the slave operates in
+//! the framed mode of the [Transceiver] and answers every datagram
+//! containing a request with a datagram containing the response.
+
+use std::{
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+use crate::slave::transceiver::{SlaveMemory, Transceiver};
+
+/// The largest datagram that can be carried over UDP
+const MAX_DATAGRAM: usize = u16::MAX as usize;
+
+/// A UDP socket that passes the received frames to a [Transceiver]
+pub struct UdpSlave {
+    socket: UdpSocket,
+    rx: Vec<u8>,
+    tx: Vec<u8>,
+}
+
+impl UdpSlave {
+    /// Binds a new socket for the slave
+    /// # Arguments
+    /// * `addr` - The local address to receive the frames on
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::from_socket(UdpSocket::bind(addr)?))
+    }
+
+    /// Creates a new slave host from an already bound socket
+    /// # Arguments
+    /// * `socket` - The socket to use
+    pub fn from_socket(socket: UdpSocket) -> Self {
+        Self {
+            socket,
+            rx: vec![0u8; MAX_DATAGRAM],
+            tx: Vec::new(),
+        }
+    }
+
+    /// Returns the local address the slave receives the frames on
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Returns the underlying socket
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Waits for a single frame, passes it to the `transceiver`
+    /// and sends the response back to the master, if there is any
+    /// # Arguments
+    /// * `transceiver` - The transceiver to handle the frame
+    /// * `timeout` - The maximum time to wait for a frame, `None` to wait forever
+    /// # Returns
+    /// Whether a frame has been received, failing if the
+    /// response could not be sent back to the master
+    pub fn poll<M: SlaveMemory>(
+        &mut self,
+        transceiver: &mut Transceiver<M>,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        self.socket.set_read_timeout(timeout)?;
+
+        let (len, src) = match self.socket.recv_from(&mut self.rx) {
+            Ok(res) => res,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                return Ok(false)
+            }
+            Err(e) => return Err(e),
+        };
+
+        self.tx.resize(transceiver.max_response_len(), 0);
+        let len = transceiver
+            .handle_frame(&self.rx[..len], &mut self.tx)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, format!("{e:?}")))?;
+        if len > 0 {
+            self.socket.send_to(&self.tx[..len], src)?;
+        }
+
+        Ok(true)
+    }
+}
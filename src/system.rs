@@ -0,0 +1,274 @@
+//! The layout of the system region in the memory area of every slave.
+//!
+//! The system region occupies the top of the memory area, starting at
+//! [SYSTEM_REGION_START]. It is served by the slave transceiver itself
+//! and never reaches the memory of the application.
+//! All multi-byte values in this region are little-endian.
+
+/// The first offset of the system region
+pub const SYSTEM_REGION_START: u16 = 0xFF00;
+
+/// The offset of the capability descriptor
+pub const CAPABILITIES_OFFSET: u16 = 0xFF00;
+/// The size of the capability descriptor
+pub const CAPABILITIES_SIZE: usize = 8;
+/// The version of the layout of the capability descriptor
+pub const CAPABILITIES_VERSION: u8 = 1;
+
+/// The offset of the diagnostics block
+pub const DIAGNOSTICS_OFFSET: u16 = 0xFF20;
+/// The size of the diagnostics block
+pub const DIAGNOSTICS_SIZE: usize = 32;
+
+/// The offset of the watchdog timeout
+pub const WATCHDOG_OFFSET: u16 = 0xFF40;
+/// The size of the watchdog timeout, in milliseconds with `0` disabling the watchdog
+pub const WATCHDOG_SIZE: usize = 2;
+
+/// The offset of the working counter configuration, holding the amount
+/// of ack slots following every memory command (`0` disabling the working
+/// counter) and the ack slot of the slave
+pub const WORKING_COUNTER_OFFSET: u16 = 0xFF44;
+/// The size of the working counter configuration
+pub const WORKING_COUNTER_SIZE: usize = 2;
+
+/// The offset of the MMU table
+pub const MMU_OFFSET: u16 = 0xFF80;
+/// The amount of MMU entries every slave provides
+pub const MMU_COUNT: usize = 4;
+/// The size of a single entry in the MMU table
+pub const MMU_ENTRY_SIZE: usize = 8;
+
+const MMU_FLAG_READ: u8 = 1 << 0;
+const MMU_FLAG_WRITE: u8 = 1 << 1;
+
+/// An entry of the MMU of a slave, mapping a window of the
+/// logical address space onto the memory area of the slave
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MmuEntry {
+    /// The first logical address of the window
+    pub logical_start: u16,
+
+    /// The length of the window in bytes
+    pub length: u16,
+
+    /// The offset in the memory area of the slave the window maps to
+    pub physical_start: u16,
+
+    /// Whether logical reads are served from this window
+    pub read: bool,
+
+    /// Whether logical writes are applied to this window
+    pub write: bool,
+}
+
+impl MmuEntry {
+    /// Serializes the entry into its representation in the MMU table
+    pub fn to_bytes(entry: Option<&Self>) -> [u8; MMU_ENTRY_SIZE] {
+        let mut bytes = [0u8; MMU_ENTRY_SIZE];
+
+        if let Some(e) = entry {
+            bytes[0..2].copy_from_slice(&e.logical_start.to_le_bytes());
+            bytes[2..4].copy_from_slice(&e.length.to_le_bytes());
+            bytes[4..6].copy_from_slice(&e.physical_start.to_le_bytes());
+            bytes[6] =
+                if e.read { MMU_FLAG_READ } else { 0 } | if e.write { MMU_FLAG_WRITE } else { 0 };
+        }
+
+        bytes
+    }
+
+    /// Parses an entry from its representation in the MMU table.
+    ///
+    /// Entries that neither allow reads nor writes are disabled.
+    pub fn from_bytes(bytes: &[u8; MMU_ENTRY_SIZE]) -> Option<Self> {
+        let flags = bytes[6];
+        if flags & (MMU_FLAG_READ | MMU_FLAG_WRITE) == 0 {
+            return None;
+        }
+
+        Some(Self {
+            logical_start: u16::from_le_bytes([bytes[0], bytes[1]]),
+            length: u16::from_le_bytes([bytes[2], bytes[3]]),
+            physical_start: u16::from_le_bytes([bytes[4], bytes[5]]),
+            read: flags & MMU_FLAG_READ != 0,
+            write: flags & MMU_FLAG_WRITE != 0,
+        })
+    }
+}
+
+/// The level of support of a slave for an optional feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureSupport {
+    /// The feature must not be used on the bus at all
+    None,
+
+    /// The feature can be used on the bus, but the slave cannot be targeted by it
+    Partial,
+
+    /// The feature can be used to target the slave
+    Full,
+}
+
+impl FeatureSupport {
+    fn from_bits(bits: u8) -> Option<Self> {
+        match bits & 0b11 {
+            0 => Some(Self::None),
+            1 => Some(Self::Partial),
+            2 => Some(Self::Full),
+            _ => None,
+        }
+    }
+
+    fn bits(&self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Partial => 1,
+            Self::Full => 2,
+        }
+    }
+}
+
+/// The capabilities of a slave, as described by its capability descriptor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlaveCapabilities {
+    /// The protocol versions the slave supports, bit `n` stands for version `n + 1`
+    pub protocol_versions: u8,
+
+    /// The size of the scratchpad, limiting the size of a single operation
+    pub scratchpad_size: u16,
+
+    /// Support for the 16-bit operation offset
+    pub long_offset: FeatureSupport,
+
+    /// Support for the 16-bit operation size
+    pub long_size: FeatureSupport,
+
+    /// Support for logical addressing
+    pub logical_addressing: FeatureSupport,
+
+    /// Support for logical memory operations using MMUs
+    pub mmu: FeatureSupport,
+
+    /// The amount of MMU entries the slave provides
+    pub mmu_count: u8,
+}
+
+impl SlaveCapabilities {
+    /// Serializes the capabilities into the capability descriptor
+    pub fn to_bytes(&self) -> [u8; CAPABILITIES_SIZE] {
+        let size = self.scratchpad_size.to_le_bytes();
+        [
+            CAPABILITIES_VERSION,
+            self.protocol_versions,
+            size[0],
+            size[1],
+            self.long_offset.bits()
+                | self.long_size.bits() << 2
+                | self.logical_addressing.bits() << 4
+                | self.mmu.bits() << 6,
+            self.mmu_count,
+            0,
+            0,
+        ]
+    }
+
+    /// Parses the capabilities from a capability descriptor
+    /// # Returns
+    /// `None` if the descriptor is malformed or of an unknown version
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < CAPABILITIES_SIZE || bytes[0] != CAPABILITIES_VERSION {
+            return None;
+        }
+
+        let features = bytes[4];
+        Some(Self {
+            protocol_versions: bytes[1],
+            scratchpad_size: u16::from_le_bytes([bytes[2], bytes[3]]),
+            long_offset: FeatureSupport::from_bits(features)?,
+            long_size: FeatureSupport::from_bits(features >> 2)?,
+            logical_addressing: FeatureSupport::from_bits(features >> 4)?,
+            mmu: FeatureSupport::from_bits(features >> 6)?,
+            mmu_count: bytes[5],
+        })
+    }
+
+    /// Returns whether the slave supports the protocol `version`
+    pub fn supports_version(&self, version: u8) -> bool {
+        (1..=8).contains(&version) && self.protocol_versions & 1 << (version - 1) != 0
+    }
+}
+
+/// The diagnostics counters of a slave, describing the health of its link.
+///
+/// All counters wrap around on overflow.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    /// The amount of frames that have been received or sent completely with a valid CRC
+    pub valid_frames: u32,
+
+    /// The amount of frame headers with a CRC mismatch
+    pub header_crc_errors: u32,
+
+    /// The amount of frames with a CRC mismatch
+    pub frame_crc_errors: u32,
+
+    /// The amount of frames with an unexpected sequence number
+    pub sequence_errors: u32,
+
+    /// The amount of unknown commands
+    pub unknown_commands: u32,
+
+    /// The amount of memory operations that have been rejected,
+    /// including operations that did not fit into the scratchpad
+    pub memory_errors: u32,
+
+    /// The amount of times the slave gained sync with the bus
+    pub sync_gained: u32,
+
+    /// The amount of times the slave lost sync with the bus
+    pub sync_lost: u32,
+}
+
+impl Diagnostics {
+    /// Serializes the counters into the diagnostics block
+    pub fn to_bytes(&self) -> [u8; DIAGNOSTICS_SIZE] {
+        let mut bytes = [0u8; DIAGNOSTICS_SIZE];
+        for (counter, chunk) in self.counters().iter().zip(bytes.chunks_mut(4)) {
+            chunk.copy_from_slice(&counter.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Parses the counters from a diagnostics block
+    /// # Returns
+    /// `None` if the block is too short
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..DIAGNOSTICS_SIZE)?;
+        let c = |i: usize| u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap());
+
+        Some(Self {
+            valid_frames: c(0),
+            header_crc_errors: c(1),
+            frame_crc_errors: c(2),
+            sequence_errors: c(3),
+            unknown_commands: c(4),
+            memory_errors: c(5),
+            sync_gained: c(6),
+            sync_lost: c(7),
+        })
+    }
+
+    fn counters(&self) -> [u32; DIAGNOSTICS_SIZE / 4] {
+        [
+            self.valid_frames,
+            self.header_crc_errors,
+            self.frame_crc_errors,
+            self.sequence_errors,
+            self.unknown_commands,
+            self.memory_errors,
+            self.sync_gained,
+            self.sync_lost,
+        ]
+    }
+}